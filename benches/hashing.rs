@@ -0,0 +1,139 @@
+//! Compares `hash_file` against hashing with fixed 1024-byte reads, the way files were hashed
+//! before buffers were sized by file length and large files were memory-mapped.
+//!
+//! The test fixtures are scaled up twice: concatenated into one large file, like a disk image, and
+//! copied many times over, like a directory of small files. Run with:
+//!
+//! ```bash
+//! cargo bench --bench hashing
+//! ```
+//!
+//! `HASH_BENCH_MIB` sets the size of the large file in MiB, 512 by default.
+
+use manifest_checker::{hash_file, HashAlgorithm};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+/// The fixtures the benchmark data is built from.
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test/SomeProject");
+
+/// How many times the small fixture files are copied.
+const SMALL_FILE_COPIES: usize = 50;
+
+/// How many times each measurement is taken, the fastest one is reported.
+const ROUNDS: usize = 3;
+
+fn main() -> io::Result<()> {
+    let large_mib: u64 = env::var("HASH_BENCH_MIB").ok().and_then(|mib| mib.parse().ok()).unwrap_or(512);
+
+    let fixtures: Vec<Vec<u8>> = WalkDir::new(FIXTURE_DIR)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| fs::read(entry.path()))
+        .collect::<io::Result<_>>()?;
+
+    let scratch = tempfile::tempdir()?;
+
+    let large_file = scratch.path().join("large.img");
+    write_large_file(&large_file, &fixtures, large_mib * 1024 * 1024)?;
+
+    let mut small_files: Vec<PathBuf> = Vec::new();
+    for copy in 0..SMALL_FILE_COPIES {
+        for (index, contents) in fixtures.iter().enumerate() {
+            let path = scratch.path().join(format!("small-{}-{}.bin", copy, index));
+            fs::write(&path, contents)?;
+            small_files.push(path);
+        }
+    }
+
+    println!("hashing with sha256, fastest of {} rounds", ROUNDS);
+    compare(&format!("1 file of {} MiB", large_mib), std::slice::from_ref(&large_file))?;
+    compare(&format!("{} fixture files", small_files.len()), &small_files)?;
+
+    Ok(())
+}
+
+/// Fills a file with the fixtures, repeated until it reaches the given size.
+fn write_large_file(path: &Path, fixtures: &[Vec<u8>], size: u64) -> io::Result<()> {
+    let mut writer  = BufWriter::new(File::create(path)?);
+    let mut written = 0;
+
+    for contents in fixtures.iter().cycle() {
+        let remaining = usize::try_from(size - written).unwrap_or(usize::MAX);
+        let chunk     = &contents[..contents.len().min(remaining)];
+        writer.write_all(chunk)?;
+        written += chunk.len() as u64;
+
+        if written >= size {
+            break;
+        }
+    }
+
+    writer.flush()
+}
+
+/// Hashes the files both ways, checks the digests agree and prints the throughput of each.
+fn compare(label: &str, files: &[PathBuf]) -> io::Result<()> {
+    let total_bytes: u64 = files.iter().map(|path| fs::metadata(path).map(|metadata| metadata.len())).sum::<io::Result<u64>>()?;
+
+    let (baseline_time, baseline_hashes) = fastest(|| files.iter().map(|path| hash_file_1k(path)).collect())?;
+    let (current_time, current_hashes)   = fastest(|| {
+        files
+            .iter()
+            .map(|path| hash_file(path, HashAlgorithm::Sha256).map_err(|error| io::Error::other(error.to_string())))
+            .collect()
+    })?;
+
+    assert_eq!(baseline_hashes, current_hashes, "both hashing paths must agree");
+
+    println!("{}:", label);
+    println!("  1 KiB reads: {:>9.1} MiB/s", throughput(total_bytes, baseline_time));
+    println!("  hash_file:   {:>9.1} MiB/s ({:.2}x)", throughput(total_bytes, current_time), baseline_time.as_secs_f64() / current_time.as_secs_f64());
+
+    Ok(())
+}
+
+/// Runs a measurement `ROUNDS` times, returning the fastest time and the hashes it produced.
+fn fastest(mut run: impl FnMut() -> io::Result<Vec<String>>) -> io::Result<(Duration, Vec<String>)> {
+    let mut best: Option<(Duration, Vec<String>)> = None;
+
+    for _ in 0..ROUNDS {
+        let start  = Instant::now();
+        let hashes = run()?;
+        let time   = start.elapsed();
+
+        if best.as_ref().is_none_or(|(best_time, _)| time < *best_time) {
+            best = Some((time, hashes));
+        }
+    }
+
+    Ok(best.expect("at least one round runs"))
+}
+
+/// Returns the throughput in MiB per second.
+fn throughput(bytes: u64, time: Duration) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0) / time.as_secs_f64()
+}
+
+/// Hashes a file with SHA256 through a fixed 1024-byte buffer, as `hash_file` used to.
+fn hash_file_1k(path: &Path) -> io::Result<String> {
+    let mut file   = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 1024];
+
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        hasher.update(&buffer[..count]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
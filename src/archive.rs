@@ -0,0 +1,873 @@
+use crate::error::{Error, Result};
+use crate::filter::{listed_ignore_file, PathFilter, IGNORE_FILE_NAME};
+use crate::hash::{hash_bytes, hash_reader, HashAlgorithm};
+use crate::manifest::{FileEntry, Manifest};
+use crate::metadata::{metadata_mismatches, EntryType, FileMetadata};
+use crate::path::{normalize_manifest_path, SymlinkPolicy};
+use crate::report::{FileReport, FileStatus, VerificationReport};
+use crate::verify::VerifyOptions;
+use flate2::read::MultiGzDecoder;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// The most links followed in a row before a symlink is treated as a loop.
+const MAX_LINK_HOPS: usize = 40;
+
+/// The archive formats that can be verified without extracting them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// An uncompressed tar archive.
+    Tar,
+    /// A gzip-compressed tar archive, such as a `.tar.gz` or `.tgz` file.
+    TarGz,
+    /// A zip archive.
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Returns the name used for this format in messages.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar   => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::Zip   => "zip",
+        }
+    }
+
+    /// Detects the format of an archive from its leading bytes. Old tar archives without the
+    /// `ustar` magic are recognized by their `.tar` extension.
+    ///
+    /// Args:
+    /// - `path`: The path of the archive file.
+    ///
+    /// Returns:
+    /// - `Result<Option<ArchiveFormat>>`: The format, `None` if the file isn't a supported archive,
+    ///   or an error if it could not be read.
+    pub fn detect(path: &Path) -> Result<Option<Self>> {
+        let mut header: Vec<u8> = Vec::with_capacity(512);
+        File::open(path)
+            .and_then(|file| file.take(512).read_to_end(&mut header))
+            .map_err(|source| Error::io(path, source))?;
+
+        let format = if header.starts_with(&[0x1f, 0x8b]) {
+            Some(ArchiveFormat::TarGz)
+        } else if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            Some(ArchiveFormat::Zip)
+        } else if header.get(257..262) == Some(b"ustar".as_slice())
+            || path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("tar"))
+        {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        };
+
+        Ok(format)
+    }
+}
+
+/// What an archive member is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MemberKind {
+    File,
+    Directory,
+    /// A symlink, with its target as stored.
+    Symlink(String),
+    /// A tar hard link, with the path of the member it links to.
+    HardLink(String),
+    /// A device, FIFO or other special file, which has no content to hash.
+    Special,
+}
+
+/// A member of an archive, recorded while streaming through it.
+#[derive(Debug)]
+struct Member {
+    /// The normalized forward-slash path of the member.
+    path: String,
+    kind: MemberKind,
+    size: u64,
+    mode: Option<u32>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    /// The digest of the content, with the algorithm it was computed with, if the manifest lists
+    /// the member as a file. Only listed members are hashed.
+    hash: Option<(HashAlgorithm, std::result::Result<String, String>)>,
+}
+
+impl Member {
+    /// Returns the member's type, permission bits and ownership, as far as the archive records them.
+    fn metadata(&self) -> FileMetadata {
+        let kind = match self.kind {
+            MemberKind::Directory  => EntryType::Directory,
+            MemberKind::Symlink(_) => EntryType::Symlink,
+            MemberKind::File | MemberKind::HardLink(_) | MemberKind::Special => EntryType::File,
+        };
+
+        FileMetadata { kind, mode: self.mode, uid: self.uid, gid: self.gid }
+    }
+}
+
+/// The members of an archive, collected in a single pass that hashes every listed file as it is
+/// streamed, so nothing is extracted to disk.
+struct ArchiveIndex<'a> {
+    archive_path: &'a Path,
+    manifest: &'a Manifest,
+    options: &'a VerifyOptions,
+    /// The manifest entries by comparable path.
+    entries: HashMap<String, &'a FileEntry>,
+    /// The members by comparable path. A later member replaces an earlier one with the same path,
+    /// as it would on extraction.
+    members: HashMap<String, Member>,
+    /// The comparable paths stored by more than one member, other than repeated directories.
+    /// Extractors disagree on which copy wins, so a listed path among them is unsafe.
+    duplicate_members: HashSet<String>,
+    /// The directories implied by member paths, by comparable path, as zip archives often have no
+    /// members for their directories.
+    implied_directories: HashSet<String>,
+    /// The stored names of members whose path escapes the archive root.
+    escaping_members: Vec<String>,
+    /// The raw contents of the archive's `.manifestignore` file, if it has one.
+    ignore_file: Option<Vec<u8>>,
+}
+
+impl<'a> ArchiveIndex<'a> {
+    /// Creates an empty index for verifying an archive against a manifest.
+    fn new(archive_path: &'a Path, manifest: &'a Manifest, options: &'a VerifyOptions) -> Self {
+        let mut index = ArchiveIndex {
+            archive_path,
+            manifest,
+            options,
+            entries: HashMap::new(),
+            members: HashMap::new(),
+            duplicate_members: HashSet::new(),
+            implied_directories: HashSet::new(),
+            escaping_members: Vec::new(),
+            ignore_file: None,
+        };
+
+        for (key, entry) in &manifest.files {
+            if let Some(path) = normalize_manifest_path(key) {
+                index.entries.insert(index.comparable(&path), entry);
+            }
+        }
+
+        index
+    }
+
+    /// Returns the form of a normalized path that members and entries are matched by.
+    fn comparable(&self, path: &str) -> String {
+        if self.options.case_insensitive { path.to_lowercase() } else { path.to_string() }
+    }
+
+    /// Records a member, hashing its content from the reader if the manifest lists it as a file.
+    ///
+    /// Args:
+    /// - `stored_path`: The path of the member as stored in the archive.
+    /// - `member`: The member, with an empty path and no hash yet.
+    /// - `reader`: The content of the member.
+    ///
+    /// Returns:
+    /// - `Result<()>`: Ok, or an error if the archive's `.manifestignore` file could not be read.
+    fn add(&mut self, stored_path: &str, mut member: Member, reader: &mut dyn Read) -> Result<()> {
+        let path = match normalize_manifest_path(stored_path) {
+            Some(path) if path.is_empty() => return Ok(()),
+            Some(path) => path,
+            None => {
+                self.escaping_members.push(stored_path.to_string());
+                return Ok(());
+            }
+        };
+
+        for (index, _) in path.match_indices('/') {
+            self.implied_directories.insert(self.comparable(&path[..index]));
+        }
+
+        // A listed ignore file is read whole, as it's needed for strict mode once every member is
+        // seen. An unlisted one is never honored, so it isn't read.
+        let key = self.comparable(&path);
+        let ignore_contents = if path == IGNORE_FILE_NAME && member.kind == MemberKind::File && self.entries.contains_key(&key) {
+            let mut contents: Vec<u8> = Vec::new();
+            reader.read_to_end(&mut contents).map_err(|source| Error::io(&self.archive_path.join(&path), source))?;
+            Some(contents)
+        } else {
+            None
+        };
+
+        if let Some(entry) = self.entries.get(&key) {
+            // A file of the wrong size fails without being hashed.
+            if entry.hash.is_some() && member.kind == MemberKind::File && entry.size.is_none_or(|size| size == member.size) {
+                let algorithm = self.manifest.algorithm_for(entry);
+                let hash = match &ignore_contents {
+                    Some(contents) => Ok(hash_bytes(contents, algorithm)),
+                    None => hash_reader(reader, algorithm).map_err(|error| Error::io(&self.archive_path.join(&path), error).to_string()),
+                };
+                member.hash = Some((algorithm, hash));
+            }
+        }
+
+        if let Some(contents) = ignore_contents {
+            self.ignore_file = Some(contents);
+        }
+
+        let is_directory = |member: &Member| member.kind == MemberKind::Directory;
+        if self.members.get(&key).is_some_and(|previous| !is_directory(previous) || !is_directory(&member)) {
+            self.duplicate_members.insert(key.clone());
+        }
+
+        member.path = path;
+        self.members.insert(key, member);
+
+        Ok(())
+    }
+
+    /// Checks a single manifest entry against the archive members. The content is checked first;
+    /// if it matches, the type, permission bits and ownership recorded in the entry are checked
+    /// next.
+    ///
+    /// Args:
+    /// - `expected_path_str`: The manifest path of the entry.
+    /// - `entry`: The manifest entry.
+    ///
+    /// Returns:
+    /// - `FileReport`: The verification result for the entry.
+    fn verify_entry(&self, expected_path_str: &str, entry: &FileEntry) -> FileReport {
+        let mut file_report = FileReport::new(expected_path_str, FileStatus::Missing);
+        file_report.expected_size = entry.size;
+
+        // Manifests are validated on read, so a path that escapes the archive is a caller error.
+        let Some(normalized_path) = normalize_manifest_path(expected_path_str) else {
+            file_report.status = FileStatus::Unsafe;
+            file_report.error  = Some("path escapes the verified directory".to_string());
+            return file_report;
+        };
+
+        // Extracting through a symlinked directory writes outside of it, so such paths are unsafe.
+        if let Some(symlinked) = self.symlinked_ancestor(&normalized_path) {
+            file_report.status = FileStatus::Unsafe;
+            file_report.error  = Some(format!("{} is a symlinked directory", symlinked));
+            return file_report;
+        }
+
+        let key = self.comparable(&normalized_path);
+        if self.duplicate_members.contains(&key) {
+            file_report.status = FileStatus::Unsafe;
+            file_report.error  = Some("stored more than once in the archive".to_string());
+            return file_report;
+        }
+
+        let member = self.members.get(&key);
+
+        match (&entry.hash, entry.kind) {
+            (Some(expected_hash), _) => self.verify_file_entry(member, entry, expected_hash, &mut file_report),
+            (None, Some(EntryType::Directory)) => self.verify_directory_entry(&normalized_path, member, entry, &mut file_report),
+            (None, _) => self.verify_symlink_entry(member, entry, &mut file_report),
+        }
+
+        file_report
+    }
+
+    /// Checks a file entry by its digest. Symlinks are followed to the member they point to under
+    /// the `Follow` policy, and hard links always are; either way the target must be listed in the
+    /// manifest with the same algorithm, as only listed members are hashed.
+    fn verify_file_entry(&self, member: Option<&Member>, entry: &FileEntry, expected_hash: &str, file_report: &mut FileReport) {
+        let algorithm = self.manifest.algorithm_for(entry);
+        file_report.algorithm     = Some(algorithm);
+        file_report.expected_hash = Some(expected_hash.to_string());
+
+        let Some(member) = member else {
+            return;
+        };
+
+        let target = match (&member.kind, self.options.symlinks) {
+            (MemberKind::Symlink(_), SymlinkPolicy::Forbid) => {
+                file_report.status = FileStatus::Unsafe;
+                file_report.error  = Some(format!("{} is a symlink", member.path));
+                return;
+            }
+            (MemberKind::Symlink(target), SymlinkPolicy::Record) => {
+                // The link target path is hashed instead of the content it points to.
+                let target_size = target.len() as u64;
+                file_report.link_target = Some(target.clone());
+                if entry.size.is_some_and(|size| size != target_size) {
+                    file_report.status      = FileStatus::SizeMismatch;
+                    file_report.actual_size = Some(target_size);
+                    return;
+                }
+
+                file_report.actual_size = entry.size.map(|_| target_size);
+                self.compare_hash(Ok(hash_bytes(target.as_bytes(), algorithm)), expected_hash, file_report);
+                self.check_metadata(Some(member), entry, file_report);
+                return;
+            }
+            _ => match self.resolve_links(member) {
+                Ok(Some(target)) => target,
+                // A dangling link is missing, as it is in a directory.
+                Ok(None) => return,
+                Err(reason) => {
+                    file_report.status = FileStatus::Unsafe;
+                    file_report.error  = Some(reason);
+                    return;
+                }
+            },
+        };
+
+        let hash = match (&target.kind, &target.hash) {
+            (MemberKind::File, _) if entry.size.is_some_and(|size| size != target.size) => {
+                file_report.status      = FileStatus::SizeMismatch;
+                file_report.actual_size = Some(target.size);
+                return;
+            }
+            (MemberKind::File, Some((hashed_with, hash))) if *hashed_with == algorithm => {
+                if hash.is_ok() {
+                    file_report.hashed_bytes = target.size;
+                }
+                hash.clone()
+            }
+            (MemberKind::File, _) => Err(format!(
+                "links to {}, which the manifest doesn't list with the same algorithm, so it was not hashed",
+                target.path
+            )),
+            (MemberKind::Directory, _) => Err("is a directory".to_string()),
+            _ => Err("is not a regular file".to_string()),
+        };
+
+        file_report.actual_size = entry.size.map(|_| target.size);
+        self.compare_hash(hash, expected_hash, file_report);
+        self.check_metadata(Some(member), entry, file_report);
+    }
+
+    /// Checks that a directory entry names a directory, either a member of its own or one implied
+    /// by the paths of the members inside it.
+    fn verify_directory_entry(&self, normalized_path: &str, member: Option<&Member>, entry: &FileEntry, file_report: &mut FileReport) {
+        file_report.status = match member.map(|member| &member.kind) {
+            Some(MemberKind::Directory) => FileStatus::Matched,
+            Some(MemberKind::Symlink(target)) => {
+                file_report.error       = Some("expected a directory, found a symlink".to_string());
+                file_report.link_target = Some(target.clone());
+                FileStatus::Mismatched
+            }
+            Some(_) => {
+                file_report.error = Some("expected a directory, found a file".to_string());
+                FileStatus::Mismatched
+            }
+            None if self.implied_directories.contains(&self.comparable(normalized_path)) => FileStatus::Matched,
+            None => return,
+        };
+
+        self.check_metadata(member, entry, file_report);
+    }
+
+    /// Checks a symlink entry by comparing its link target exactly. Under the `Forbid` policy the
+    /// entry is reported as unsafe.
+    fn verify_symlink_entry(&self, member: Option<&Member>, entry: &FileEntry, file_report: &mut FileReport) {
+        let Some(member) = member else {
+            return;
+        };
+        let expected_target = entry.target.clone().unwrap_or_default();
+
+        file_report.status = match &member.kind {
+            MemberKind::Symlink(_) if self.options.symlinks == SymlinkPolicy::Forbid => {
+                file_report.status = FileStatus::Unsafe;
+                file_report.error  = Some(format!("{} is a symlink", member.path));
+                return;
+            }
+            MemberKind::Symlink(target) => {
+                file_report.link_target = Some(target.clone());
+                if *target == expected_target { FileStatus::Matched } else { FileStatus::Mismatched }
+            }
+            MemberKind::Directory => {
+                file_report.error = Some("expected a symlink, found a directory".to_string());
+                FileStatus::Mismatched
+            }
+            _ => {
+                file_report.error = Some("expected a symlink, found a file".to_string());
+                FileStatus::Mismatched
+            }
+        };
+        file_report.expected_target = Some(expected_target);
+
+        self.check_metadata(Some(member), entry, file_report);
+    }
+
+    /// Sets the report's status from a computed digest, or marks it unreadable.
+    fn compare_hash(&self, hash: std::result::Result<String, String>, expected_hash: &str, file_report: &mut FileReport) {
+        match hash {
+            Ok(hash) => {
+                file_report.status = if hash.eq_ignore_ascii_case(expected_hash) {
+                    FileStatus::Matched
+                } else {
+                    FileStatus::Mismatched
+                };
+                file_report.actual_hash = Some(hash);
+            }
+            Err(error) => {
+                file_report.status = FileStatus::Unreadable;
+                file_report.error  = Some(error);
+            }
+        }
+    }
+
+    /// Checks the type, permission bits and ownership recorded in an entry whose content has been
+    /// checked, unless metadata is ignored. Fields the archive doesn't record, such as ownership in
+    /// zip archives, are not compared. A matching entry with metadata differences becomes a
+    /// metadata mismatch.
+    fn check_metadata(&self, member: Option<&Member>, entry: &FileEntry, file_report: &mut FileReport) {
+        if self.options.ignore_metadata || file_report.status == FileStatus::Unreadable {
+            return;
+        }
+
+        let metadata = member.map_or(
+            FileMetadata { kind: EntryType::Directory, mode: None, uid: None, gid: None },
+            Member::metadata,
+        );
+        file_report.metadata_mismatches = metadata_mismatches(entry, &metadata);
+
+        if file_report.status == FileStatus::Matched && !file_report.metadata_mismatches.is_empty() {
+            file_report.status = FileStatus::MetadataMismatch;
+        }
+    }
+
+    /// Follows symlinks and hard links from a member to the member they finally point to. Symlink
+    /// targets are resolved against the link's directory and must stay inside the archive; hard
+    /// link targets are archive paths.
+    ///
+    /// Returns:
+    /// - `std::result::Result<Option<&Member>, String>`: The final member, which may be the given
+    ///   one, `None` if a link points to a path that isn't in the archive, or why the links can't
+    ///   be followed safely.
+    fn resolve_links<'m>(&'m self, mut member: &'m Member) -> std::result::Result<Option<&'m Member>, String> {
+        for _ in 0..MAX_LINK_HOPS {
+            let target_path = match &member.kind {
+                MemberKind::Symlink(target) if target.starts_with('/') => {
+                    return Err(format!("{} points to {}, outside the archive", member.path, target));
+                }
+                MemberKind::Symlink(target) => match member.path.rsplit_once('/') {
+                    Some((parent, _)) => format!("{}/{}", parent, target),
+                    None => target.clone(),
+                },
+                MemberKind::HardLink(target) => target.clone(),
+                _ => return Ok(Some(member)),
+            };
+
+            let Some(target_path) = normalize_manifest_path(&target_path) else {
+                return Err(format!("{} points outside the archive", member.path));
+            };
+
+            match self.members.get(&self.comparable(&target_path)) {
+                Some(target) => member = target,
+                None => return Ok(None),
+            }
+        }
+
+        Err(format!("{} is part of a symlink loop", member.path))
+    }
+
+    /// Returns the first directory above a path that is a symlink member, if any.
+    fn symlinked_ancestor<'p>(&self, normalized_path: &'p str) -> Option<&'p str> {
+        normalized_path.match_indices('/').map(|(index, _)| &normalized_path[..index]).find(|ancestor| {
+            self.members
+                .get(&self.comparable(ancestor))
+                .is_some_and(|member| matches!(member.kind, MemberKind::Symlink(_)))
+        })
+    }
+
+    /// Reports every member that isn't a directory and isn't listed in the manifest, unless the
+    /// filter leaves it out.
+    ///
+    /// Symlinks are reported like files, except under the `Follow` policy, where only symlinks to
+    /// files are, as when walking a directory.
+    fn unlisted_members(&self, filter: &PathFilter) -> Vec<FileReport> {
+        let mut file_reports: Vec<FileReport> = Vec::new();
+
+        for (key, member) in &self.members {
+            let listed = match &member.kind {
+                MemberKind::Directory => false,
+                MemberKind::Symlink(_) if self.options.symlinks == SymlinkPolicy::Follow => {
+                    matches!(self.resolve_links(member), Ok(Some(target)) if target.kind == MemberKind::File)
+                }
+                _ => true,
+            };
+
+            if listed && !self.entries.contains_key(key) && !filter.is_excluded(&member.path, false) {
+                file_reports.push(FileReport::new(member.path.clone(), FileStatus::Extra));
+            }
+        }
+
+        file_reports
+    }
+
+    /// Reports every member whose path escapes the archive root, as extracting the archive would
+    /// write outside the target directory. These are reported whether or not the mode is strict.
+    fn escaping_members(&self) -> Vec<FileReport> {
+        self.escaping_members
+            .iter()
+            .map(|stored_path| {
+                let mut file_report = FileReport::new(stored_path.clone(), FileStatus::Unsafe);
+                file_report.error = Some("archive member escapes the archive root".to_string());
+                file_report
+            })
+            .collect()
+    }
+}
+
+/// Verifies the members of a tar, tar.gz or zip archive against the manifest, as if it were the
+/// extracted directory, without extracting anything to disk.
+///
+/// The archive is streamed once and every member the manifest lists as a file is hashed as it
+/// passes, so `options.jobs` doesn't apply. Statuses are reported as for `verify_directory`; in
+/// strict mode, the archive's own `.manifestignore` file is honored if the manifest lists it and
+/// its hash matches.
+///
+/// Symlinks to files are followed inside the archive under the `Follow` policy, as long as the
+/// file they point to is listed with the same algorithm, since unlisted members are never hashed.
+/// Paths through a symlinked directory, and members whose path escapes the archive root through
+/// `..`, are always unsafe, as extracting them would write outside the directory. So is a listed
+/// path stored by more than one member, as which copy is extracted depends on the tool.
+///
+/// Args:
+/// - `archive_path`: Path to the archive containing the files to verify.
+/// - `manifest`: The manifest containing expected file hashes.
+/// - `options`: Options controlling strict mode, symlink and metadata handling.
+///
+/// Returns:
+/// - `Result<VerificationReport>`: The report for every checked path, or an error if the file is
+///   not a supported archive, is corrupt, or the path rules are invalid in strict mode.
+pub fn verify_archive(archive_path: &Path, manifest: &Manifest, options: &VerifyOptions) -> Result<VerificationReport> {
+    let format = ArchiveFormat::detect(archive_path)?
+        .ok_or_else(|| Error::archive(archive_path, "not a tar, tar.gz or zip archive"))?;
+
+    let archive_file = File::open(archive_path).map_err(|source| Error::io(archive_path, source))?;
+    let mut index    = ArchiveIndex::new(archive_path, manifest, options);
+
+    match format {
+        ArchiveFormat::Tar   => read_tar(BufReader::new(archive_file), &mut index)?,
+        ArchiveFormat::TarGz => read_tar(MultiGzDecoder::new(BufReader::new(archive_file)), &mut index)?,
+        ArchiveFormat::Zip   => read_zip(BufReader::new(archive_file), &mut index)?,
+    }
+
+    let mut report = VerificationReport::default();
+    for (key, entry) in &manifest.files {
+        report.files.push(index.verify_entry(key, entry));
+    }
+
+    if options.strict {
+        // As in a directory, the archive's ignore file only applies if the manifest lists it.
+        let ignore_path     = archive_path.join(IGNORE_FILE_NAME);
+        let ignore_contents = listed_ignore_file(manifest, index.ignore_file.as_deref());
+        let filter          = PathFilter::with_ignore_file(&options.paths, &manifest.ignore, &ignore_path, ignore_contents.as_deref())?;
+        report.files.extend(index.unlisted_members(&filter));
+    }
+
+    // Extracting the archive would write these outside the target, so they always fail.
+    report.files.extend(index.escaping_members());
+
+    // Keep the report independent of the manifest's map ordering and the archive's member order.
+    report.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(report)
+}
+
+/// Streams the members of a tar archive into the index.
+///
+/// Args:
+/// - `reader`: The tar archive, already decompressed.
+/// - `index`: The index the members are recorded in.
+///
+/// Returns:
+/// - `Result<()>`: Ok, or an error if the archive is corrupt.
+fn read_tar<R: Read>(reader: R, index: &mut ArchiveIndex) -> Result<()> {
+    let archive_path = index.archive_path;
+    let archive_error = |error: io::Error| Error::archive(archive_path, error.to_string());
+
+    let mut archive = tar::Archive::new(reader);
+
+    for tar_entry in archive.entries().map_err(archive_error)? {
+        let mut tar_entry = tar_entry.map_err(archive_error)?;
+        let header        = tar_entry.header();
+        let entry_type    = header.entry_type();
+
+        // Global pax headers carry defaults for later members, not a member of their own.
+        if entry_type.is_pax_global_extensions() {
+            continue;
+        }
+
+        let link_name = || String::from_utf8_lossy(&tar_entry.link_name_bytes().unwrap_or_default()).into_owned();
+        let kind = match entry_type {
+            tar::EntryType::Regular | tar::EntryType::Continuous => MemberKind::File,
+            tar::EntryType::Directory => MemberKind::Directory,
+            tar::EntryType::Symlink => MemberKind::Symlink(link_name()),
+            tar::EntryType::Link => MemberKind::HardLink(link_name()),
+            _ => MemberKind::Special,
+        };
+
+        let member = Member {
+            path: String::new(),
+            kind,
+            size: tar_entry.size(),
+            mode: header.mode().ok().map(|mode| mode & 0o7777),
+            uid: header.uid().ok().and_then(|uid| u32::try_from(uid).ok()),
+            gid: header.gid().ok().and_then(|gid| u32::try_from(gid).ok()),
+            hash: None,
+        };
+
+        let stored_path = String::from_utf8_lossy(&tar_entry.path_bytes()).into_owned();
+        index.add(&stored_path, member, &mut tar_entry)?;
+    }
+
+    // Read past the end-of-archive marker, so a truncated or corrupt compressed stream is noticed.
+    io::copy(&mut archive.into_inner(), &mut io::sink()).map_err(archive_error)?;
+
+    Ok(())
+}
+
+/// Reads the members of a zip archive into the index, decompressing each one as it is hashed.
+///
+/// Args:
+/// - `reader`: The zip archive.
+/// - `index`: The index the members are recorded in.
+///
+/// Returns:
+/// - `Result<()>`: Ok, or an error if the archive is corrupt or a member is encrypted.
+fn read_zip<R: Read + io::Seek>(reader: R, index: &mut ArchiveIndex) -> Result<()> {
+    let archive_path = index.archive_path;
+    let archive_error = |error: zip::result::ZipError| Error::archive(archive_path, error.to_string());
+
+    let mut archive = zip::ZipArchive::new(reader).map_err(archive_error)?;
+
+    for member_index in 0..archive.len() {
+        let mut zip_file = archive.by_index(member_index).map_err(archive_error)?;
+        let stored_path  = zip_file.name().to_string();
+
+        let kind = if zip_file.is_dir() {
+            MemberKind::Directory
+        } else if zip_file.is_symlink() {
+            // Zip archives store the link target as the member's content.
+            let mut target = String::new();
+            zip_file.read_to_string(&mut target).map_err(|error| Error::archive(archive_path, error.to_string()))?;
+            MemberKind::Symlink(target)
+        } else {
+            MemberKind::File
+        };
+
+        let member = Member {
+            path: String::new(),
+            kind,
+            size: zip_file.size(),
+            mode: zip_file.unix_mode().map(|mode| mode & 0o7777),
+            uid: None,
+            gid: None,
+            hash: None,
+        };
+
+        index.add(&stored_path, member, &mut zip_file)?;
+    }
+
+    Ok(())
+}
+
+/// Reads the contents of regular files at the root of an archive, such as a manifest shipped
+/// inside it. Like `verify_archive`, this streams the whole archive, and a later member replaces an
+/// earlier one with the same path.
+///
+/// Args:
+/// - `archive_path`: Path to the archive.
+/// - `names`: The names of the files to read, relative to the archive root.
+///
+/// Returns:
+/// - `Result<HashMap<String, Vec<u8>>>`: The contents of the files that were found, by name, or an
+///   error if the file is not a supported archive or is corrupt.
+pub(crate) fn read_root_files(archive_path: &Path, names: &[String]) -> Result<HashMap<String, Vec<u8>>> {
+    let format = ArchiveFormat::detect(archive_path)?
+        .ok_or_else(|| Error::archive(archive_path, "not a tar, tar.gz or zip archive"))?;
+
+    let archive_file = File::open(archive_path).map_err(|source| Error::io(archive_path, source))?;
+    let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+
+    let mut read_member = |stored_path: &str, reader: &mut dyn Read| -> Result<()> {
+        let Some(path) = normalize_manifest_path(stored_path).filter(|path| names.contains(path)) else {
+            return Ok(());
+        };
+
+        let mut contents: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut contents).map_err(|source| Error::archive(archive_path, format!("cannot read {}: {}", path, source)))?;
+        files.insert(path, contents);
+        Ok(())
+    };
+
+    match format {
+        ArchiveFormat::Tar   => visit_tar_files(archive_path, BufReader::new(archive_file), &mut read_member)?,
+        ArchiveFormat::TarGz => visit_tar_files(archive_path, MultiGzDecoder::new(BufReader::new(archive_file)), &mut read_member)?,
+        ArchiveFormat::Zip   => visit_zip_files(archive_path, BufReader::new(archive_file), &mut read_member)?,
+    }
+
+    Ok(files)
+}
+
+/// Calls `visit` with the stored path and content of every regular file in a tar archive.
+fn visit_tar_files<R: Read>(archive_path: &Path, reader: R, visit: &mut dyn FnMut(&str, &mut dyn Read) -> Result<()>) -> Result<()> {
+    let archive_error = |error: io::Error| Error::archive(archive_path, error.to_string());
+
+    let mut archive = tar::Archive::new(reader);
+
+    for tar_entry in archive.entries().map_err(archive_error)? {
+        let mut tar_entry = tar_entry.map_err(archive_error)?;
+        if matches!(tar_entry.header().entry_type(), tar::EntryType::Regular | tar::EntryType::Continuous) {
+            let stored_path = String::from_utf8_lossy(&tar_entry.path_bytes()).into_owned();
+            visit(&stored_path, &mut tar_entry)?;
+        }
+    }
+
+    io::copy(&mut archive.into_inner(), &mut io::sink()).map_err(archive_error)?;
+
+    Ok(())
+}
+
+/// Calls `visit` with the stored path and content of every regular file in a zip archive.
+fn visit_zip_files<R: Read + io::Seek>(archive_path: &Path, reader: R, visit: &mut dyn FnMut(&str, &mut dyn Read) -> Result<()>) -> Result<()> {
+    let archive_error = |error: zip::result::ZipError| Error::archive(archive_path, error.to_string());
+
+    let mut archive = zip::ZipArchive::new(reader).map_err(archive_error)?;
+
+    for member_index in 0..archive.len() {
+        let mut zip_file = archive.by_index(member_index).map_err(archive_error)?;
+        if !zip_file.is_dir() && !zip_file.is_symlink() {
+            let stored_path = zip_file.name().to_string();
+            visit(&stored_path, &mut zip_file)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use indexmap::IndexMap;
+    use std::fs;
+    use std::io::{Cursor, Write};
+    use std::path::PathBuf;
+
+    const FORMATS: [ArchiveFormat; 3] = [ArchiveFormat::Tar, ArchiveFormat::TarGz, ArchiveFormat::Zip];
+
+    /// Builds an archive of regular files in memory and writes it into a new directory. Paths are
+    /// stored exactly as given, so they can escape the root or repeat.
+    fn archive(format: ArchiveFormat, members: &[(&str, &str)]) -> (tempfile::TempDir, PathBuf) {
+        let tar_bytes = || {
+            let mut builder = tar::Builder::new(Vec::new());
+            for (path, contents) in members {
+                let mut header = tar::Header::new_gnu();
+                header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append(&header, contents.as_bytes()).unwrap();
+            }
+            builder.into_inner().unwrap()
+        };
+
+        let bytes = match format {
+            ArchiveFormat::Tar => tar_bytes(),
+            ArchiveFormat::TarGz => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&tar_bytes()).unwrap();
+                encoder.finish().unwrap()
+            }
+            ArchiveFormat::Zip => {
+                let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+                for (path, contents) in members {
+                    writer.start_file(*path, zip::write::SimpleFileOptions::default()).unwrap();
+                    writer.write_all(contents.as_bytes()).unwrap();
+                }
+                writer.finish().unwrap().into_inner()
+            }
+        };
+
+        let directory    = tempfile::tempdir().unwrap();
+        let archive_path = directory.path().join(format!("bundle.{}", format.as_str()));
+        fs::write(&archive_path, bytes).unwrap();
+
+        (directory, archive_path)
+    }
+
+    /// Returns a manifest listing the files with their SHA256 digests.
+    fn manifest_of(files: &[(&str, &str)]) -> Manifest {
+        let files = files
+            .iter()
+            .map(|(path, contents)| (path.to_string(), FileEntry::new(hash_bytes(contents.as_bytes(), HashAlgorithm::Sha256))))
+            .collect();
+
+        Manifest {
+            algorithm: None,
+            files,
+            ignore: Vec::new(),
+            signature: None,
+            extra: IndexMap::new(),
+            format: crate::manifest::ManifestFormat::Json,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Verifies an archive of the members against a manifest of the files, in strict mode or not,
+    /// and returns each reported path with its status.
+    fn verified(format: ArchiveFormat, members: &[(&str, &str)], files: &[(&str, &str)], strict: bool) -> Vec<(String, FileStatus)> {
+        let (_directory, archive_path) = archive(format, members);
+        assert_eq!(ArchiveFormat::detect(&archive_path).unwrap(), Some(format));
+
+        let options = VerifyOptions { strict, ..VerifyOptions::default() };
+        let report  = verify_archive(&archive_path, &manifest_of(files), &options).unwrap();
+        report.files.into_iter().map(|file| (file.path, file.status)).collect()
+    }
+
+    /// Pairs each path with a status, for comparing against `verified`.
+    fn expected(statuses: &[(&str, FileStatus)]) -> Vec<(String, FileStatus)> {
+        statuses.iter().map(|(path, status)| (path.to_string(), *status)).collect()
+    }
+
+    #[test]
+    fn archives_matching_the_manifest_pass() {
+        let files = [("a.txt", "a"), ("dir/b.txt", "b")];
+
+        for format in FORMATS {
+            let statuses = verified(format, &files, &files, true);
+            assert_eq!(statuses, expected(&[("a.txt", FileStatus::Matched), ("dir/b.txt", FileStatus::Matched)]), "{}", format.as_str());
+        }
+    }
+
+    #[test]
+    fn changed_members_are_mismatched() {
+        for format in FORMATS {
+            let statuses = verified(format, &[("a.txt", "changed")], &[("a.txt", "a")], true);
+            assert_eq!(statuses, expected(&[("a.txt", FileStatus::Mismatched)]), "{}", format.as_str());
+        }
+    }
+
+    #[test]
+    fn listed_files_without_a_member_are_missing() {
+        for format in FORMATS {
+            let statuses = verified(format, &[("a.txt", "a")], &[("a.txt", "a"), ("b.txt", "b")], true);
+            assert_eq!(statuses, expected(&[("a.txt", FileStatus::Matched), ("b.txt", FileStatus::Missing)]), "{}", format.as_str());
+        }
+    }
+
+    #[test]
+    fn members_escaping_the_root_are_unsafe_without_strict_mode() {
+        for format in FORMATS {
+            let statuses = verified(format, &[("a.txt", "a"), ("../escape.txt", "x")], &[("a.txt", "a")], false);
+            assert_eq!(statuses, expected(&[("../escape.txt", FileStatus::Unsafe), ("a.txt", FileStatus::Matched)]), "{}", format.as_str());
+        }
+    }
+
+    #[test]
+    fn listed_paths_stored_twice_are_unsafe() {
+        for format in FORMATS {
+            let statuses = verified(format, &[("a.txt", "a"), ("./a.txt", "evil")], &[("a.txt", "a")], false);
+            assert_eq!(statuses, expected(&[("a.txt", FileStatus::Unsafe)]), "{}", format.as_str());
+        }
+
+        // The later copy, which most extractors keep, matches, and the path still fails.
+        let statuses = verified(ArchiveFormat::Tar, &[("a.txt", "evil"), ("a.txt", "a")], &[("a.txt", "a")], false);
+        assert_eq!(statuses, expected(&[("a.txt", FileStatus::Unsafe)]));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::error::{Error, Result};
+use crate::hash::HashAlgorithm;
+use crate::manifest::{FileEntry, Manifest, ManifestFormat};
+use indexmap::IndexMap;
+use std::path::Path;
+
+/// The algorithms a checksum list can hold, told apart by the length of their digests. Lists of
+/// 64-character digests are read as SHA256, as written by `sha256sum` and `shasum -a 256`.
+const CHECKSUM_ALGORITHMS: [HashAlgorithm; 3] = [HashAlgorithm::Sha256, HashAlgorithm::Sha512, HashAlgorithm::Sha1];
+
+/// Parses a checksum list in the coreutils `sha256sum` format, one `<hex>  <path>` line per file.
+///
+/// A `*` in place of the second space marks a file hashed in binary mode, which makes no difference
+/// to the digest. Lines starting with a backslash have backslashes and newlines in their path
+/// escaped, as `sha256sum` writes them. The algorithm is told from the length of the digests, so
+/// every line must use the same one.
+///
+/// Args:
+/// - `manifest_path`: The path the list is read from, used in error messages.
+/// - `contents`: The checksum list.
+///
+/// Returns:
+/// - `Result<Manifest>`: The manifest listing every file, or a parse error naming the bad line.
+pub(crate) fn parse_checksums(manifest_path: &Path, contents: &str) -> Result<Manifest> {
+    let mut files: IndexMap<String, FileEntry> = IndexMap::new();
+    let mut algorithm: Option<HashAlgorithm>   = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let parse_error = |message: String| Error::Parse {
+            path: manifest_path.to_path_buf(),
+            line: index + 1,
+            column: 1,
+            key: None,
+            message,
+        };
+
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        let (hash, path) = line
+            .split_once(' ')
+            .and_then(|(hash, rest)| Some((hash, rest.strip_prefix([' ', '*'])?)))
+            .ok_or_else(|| parse_error("expected a digest, two spaces or a space and `*`, and a path".to_string()))?;
+
+        if !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(parse_error(format!("digest \"{}\" is not hexadecimal", hash)));
+        }
+
+        let line_algorithm = CHECKSUM_ALGORITHMS
+            .into_iter()
+            .find(|algorithm| algorithm.hex_len() == hash.len())
+            .ok_or_else(|| parse_error(format!("a digest of {} hex characters doesn't match a supported algorithm", hash.len())))?;
+
+        match algorithm {
+            Some(algorithm) if algorithm != line_algorithm => {
+                return Err(parse_error(format!("{} digest in a list of {} digests", line_algorithm, algorithm)));
+            }
+            _ => algorithm = Some(line_algorithm),
+        }
+
+        let path = if escaped { unescape_path(path).map_err(parse_error)? } else { path.to_string() };
+
+        if files.insert(path.clone(), FileEntry::new(hash)).is_some() {
+            return Err(Error::InvalidEntry {
+                path: manifest_path.to_path_buf(),
+                key: path,
+                reason: "path is listed twice".to_string(),
+            });
+        }
+    }
+
+    Ok(Manifest {
+        algorithm: algorithm.filter(|algorithm| *algorithm != HashAlgorithm::default()),
+        files,
+        ignore: Vec::new(),
+        signature: None,
+        extra: IndexMap::new(),
+        format: ManifestFormat::Sha256sum,
+        warnings: Vec::new(),
+    })
+}
+
+/// Serializes a manifest as a checksum list in the coreutils `sha256sum` format, in the manifest's
+/// order. Only digests are written: sizes, metadata and the `ignore` list are left out, and
+/// directory entries, which have no digest, are skipped.
+///
+/// Args:
+/// - `manifest_path`: The path the list is written to, used in error messages.
+/// - `manifest`: The manifest to serialize.
+///
+/// Returns:
+/// - `Result<String>`: The checksum list, or an error if the manifest has symlink entries, entries
+///   hashed with different algorithms, or an embedded signature, which the format can't hold.
+pub(crate) fn serialize_checksums(manifest_path: &Path, manifest: &Manifest) -> Result<String> {
+    if manifest.signature.is_some() {
+        return Err(Error::manifest(
+            manifest_path,
+            "the sha256sum format can't hold an embedded signature, use a detached signature instead",
+        ));
+    }
+
+    let mut text = String::new();
+    let mut list_algorithm: Option<HashAlgorithm> = None;
+
+    for (key, entry) in &manifest.files {
+        let invalid_entry = |reason: String| Error::InvalidEntry { path: manifest_path.to_path_buf(), key: key.clone(), reason };
+
+        let Some(hash) = &entry.hash else {
+            if entry.target.is_some() {
+                return Err(invalid_entry("symlink entries can't be written in the sha256sum format".to_string()));
+            }
+            continue;
+        };
+
+        let algorithm = manifest.algorithm_for(entry);
+        match list_algorithm {
+            Some(list_algorithm) if list_algorithm != algorithm => {
+                return Err(invalid_entry(format!(
+                    "{} entry in a list of {} entries, the sha256sum format holds a single algorithm",
+                    algorithm, list_algorithm
+                )));
+            }
+            _ => list_algorithm = Some(algorithm),
+        }
+
+        // Paths with a backslash or line break are escaped, and their line marked with a backslash.
+        if key.contains(['\\', '\n', '\r']) {
+            let escaped = key.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r");
+            text.push_str(&format!("\\{}  {}\n", hash.to_ascii_lowercase(), escaped));
+        } else {
+            text.push_str(&format!("{}  {}\n", hash.to_ascii_lowercase(), key));
+        }
+    }
+
+    Ok(text)
+}
+
+/// Reverses the escaping of a path on a checksum line marked with a backslash.
+///
+/// Args:
+/// - `escaped`: The escaped path.
+///
+/// Returns:
+/// - `std::result::Result<String, String>`: The path, or why the escaping is invalid.
+fn unescape_path(escaped: &str) -> std::result::Result<String, String> {
+    let mut path  = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            path.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('\\') => path.push('\\'),
+            Some('n')  => path.push('\n'),
+            Some('r')  => path.push('\r'),
+            Some(other) => return Err(format!("unknown escape `\\{}` in path", other)),
+            None => return Err("path ends with a lone backslash".to_string()),
+        }
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::read_manifest;
+    use crate::signature::TrustPolicy;
+    use std::fs;
+
+    const SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn unescape_keeps_plain_paths() {
+        assert_eq!(unescape_path("dir/file name.txt"), Ok("dir/file name.txt".to_string()));
+        assert_eq!(unescape_path(""), Ok(String::new()));
+    }
+
+    #[test]
+    fn unescape_reverses_backslash_and_line_break_escapes() {
+        assert_eq!(unescape_path("back\\\\slash"), Ok("back\\slash".to_string()));
+        assert_eq!(unescape_path("line\\nbreak\\r"), Ok("line\nbreak\r".to_string()));
+        assert_eq!(unescape_path("\\\\n"), Ok("\\n".to_string()));
+    }
+
+    #[test]
+    fn unescape_rejects_unknown_and_unfinished_escapes() {
+        assert_eq!(unescape_path("tab\\there"), Err("unknown escape `\\t` in path".to_string()));
+        assert_eq!(unescape_path("trailing\\"), Err("path ends with a lone backslash".to_string()));
+    }
+
+    #[test]
+    fn escaped_paths_round_trip_through_a_checksum_list() {
+        let directory = tempfile::tempdir().unwrap();
+        let list_path = directory.path().join("SHA256SUMS");
+        let list      = format!("{}  plain.txt\n\\{}  new\\nline\\\\back\n", SHA256, SHA256);
+        fs::write(&list_path, &list).unwrap();
+
+        let manifest: Manifest = read_manifest(&list_path, &TrustPolicy::default()).unwrap();
+        let keys: Vec<&str>    = manifest.files.keys().map(String::as_str).collect();
+
+        if cfg!(unix) {
+            assert_eq!(keys, ["plain.txt", "new\nline\\back"]);
+            assert!(manifest.warnings.is_empty());
+            assert_eq!(serialize_checksums(&list_path, &manifest).unwrap(), list);
+        } else {
+            assert_eq!(keys, ["plain.txt", "new\nline/back"]);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::manifest::{FileEntry, Manifest};
+use crate::path::normalize_manifest_path;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How an entry differs between two manifests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// The entry is only in the new manifest.
+    Added,
+    /// The entry is only in the old manifest.
+    Removed,
+    /// The entry is in both manifests, but its hash, size, metadata or target differ.
+    Changed,
+}
+
+impl ChangeKind {
+    /// Returns the snake_case name used for this change in machine-readable output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added   => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// A single entry that differs between two manifests.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryChange {
+    /// Forward-slash path of the entry, normalized so equivalent keys compare equal.
+    pub path: String,
+    /// How the entry differs.
+    pub change: ChangeKind,
+    /// The entry in the old manifest with its algorithm resolved, unless it was added.
+    pub old: Option<FileEntry>,
+    /// The entry in the new manifest with its algorithm resolved, unless it was removed.
+    pub new: Option<FileEntry>,
+    /// The fields of a changed entry that differ, one description per field.
+    pub differences: Vec<String>,
+}
+
+/// The entries that differ between two manifests, sorted by path.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ManifestDiff {
+    pub changes: Vec<EntryChange>,
+}
+
+impl ManifestDiff {
+    /// Returns `true` if both manifests describe the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns an iterator over the entries with the given kind of change.
+    ///
+    /// Args:
+    /// - `change`: The kind of change to filter by.
+    pub fn with_change(&self, change: ChangeKind) -> impl Iterator<Item = &EntryChange> {
+        self.changes.iter().filter(move |entry| entry.change == change)
+    }
+}
+
+/// Compares the entries of two manifests.
+///
+/// Entries are matched by their normalized path, and compared by algorithm and hash, size,
+/// metadata and symlink target. Entries that only differ in how they are written, such as a
+/// manifest-wide algorithm versus a per-entry one, or the case of a hash, compare equal.
+///
+/// Args:
+/// - `old`: The earlier manifest.
+/// - `new`: The later manifest.
+///
+/// Returns:
+/// - `ManifestDiff`: The added, removed and changed entries, sorted by path.
+pub fn diff_manifests(old: &Manifest, new: &Manifest) -> ManifestDiff {
+    let old_entries = resolved_entries(old);
+    let new_entries = resolved_entries(new);
+    let paths: BTreeSet<&String> = old_entries.keys().chain(new_entries.keys()).collect();
+
+    let mut diff = ManifestDiff::default();
+
+    for path in paths {
+        let old_entry = old_entries.get(path);
+        let new_entry = new_entries.get(path);
+
+        let (change, differences) = match (old_entry, new_entry) {
+            (None, _) => (ChangeKind::Added, Vec::new()),
+            (_, None) => (ChangeKind::Removed, Vec::new()),
+            (Some(old_entry), Some(new_entry)) => match entry_differences(old_entry, new_entry) {
+                differences if differences.is_empty() => continue,
+                differences => (ChangeKind::Changed, differences),
+            },
+        };
+
+        diff.changes.push(EntryChange {
+            path: path.clone(),
+            change,
+            old: old_entry.cloned(),
+            new: new_entry.cloned(),
+            differences,
+        });
+    }
+
+    diff
+}
+
+/// Returns the manifest's entries keyed by normalized path, with each hash lowercased and
+/// labelled with the algorithm it was computed with.
+fn resolved_entries(manifest: &Manifest) -> BTreeMap<String, FileEntry> {
+    manifest
+        .files
+        .iter()
+        .map(|(key, entry)| {
+            let mut entry = entry.clone();
+            if entry.hash.is_some() {
+                entry.algorithm = Some(manifest.algorithm_for(&entry));
+                entry.hash      = entry.hash.map(|hash| hash.to_ascii_lowercase());
+            }
+
+            (normalize_manifest_path(key).unwrap_or_else(|| key.clone()), entry)
+        })
+        .collect()
+}
+
+/// Describes every field that differs between two entries for the same path. Sizes and metadata
+/// are only compared when both entries record them, so recording more details isn't a change.
+///
+/// Args:
+/// - `old`: The resolved entry in the old manifest.
+/// - `new`: The resolved entry in the new manifest.
+///
+/// Returns:
+/// - `Vec<String>`: One description per differing field, such as `size: 10 -> 12`.
+fn entry_differences(old: &FileEntry, new: &FileEntry) -> Vec<String> {
+    let mut differences: Vec<String> = Vec::new();
+
+    let labelled_hash = |entry: &FileEntry| entry.hash.as_ref().map(|hash| format!("{}:{}", entry.algorithm.unwrap_or_default(), hash));
+    let octal_mode    = |entry: &FileEntry| entry.mode.map(|mode| format!("{:04o}", mode));
+
+    // (field, old value, new value, whether the field is compared when only one side records it)
+    let fields: [(&str, Option<String>, Option<String>, bool); 7] = [
+        ("hash", labelled_hash(old), labelled_hash(new), true),
+        ("target", old.target.clone(), new.target.clone(), true),
+        ("size", old.size.map(|size| size.to_string()), new.size.map(|size| size.to_string()), false),
+        ("mode", octal_mode(old), octal_mode(new), false),
+        ("uid", old.uid.map(|uid| uid.to_string()), new.uid.map(|uid| uid.to_string()), false),
+        ("gid", old.gid.map(|gid| gid.to_string()), new.gid.map(|gid| gid.to_string()), false),
+        ("type", old.kind.map(|kind| kind.to_string()), new.kind.map(|kind| kind.to_string()), false),
+    ];
+
+    for (field, old_value, new_value, always) in fields {
+        let recorded = always || (old_value.is_some() && new_value.is_some());
+        if recorded && old_value != new_value {
+            differences.push(format!(
+                "{}: {} -> {}",
+                field,
+                old_value.as_deref().unwrap_or("none"),
+                new_value.as_deref().unwrap_or("none")
+            ));
+        }
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render_diff, OutputFormat};
+
+    /// Reads a manifest from JSON, without validating its hashes.
+    fn manifest(json: &str) -> Manifest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    /// Returns the diff of two manifests where `a.txt` is unchanged, `b.txt` is removed, `c.txt`
+    /// changed and `d.txt` is added.
+    fn sample_diff() -> ManifestDiff {
+        let old = manifest(r#"{ "files": { "a.txt": "aa", "b.txt": "bb", "c.txt": { "sha256": "cc", "size": 1 } } }"#);
+        let new = manifest(r#"{ "files": { "./a.txt": "AA", "c.txt": { "sha256": "ce", "size": 2 }, "d.txt": "dd" } }"#);
+
+        diff_manifests(&old, &new)
+    }
+
+    #[test]
+    fn identical_manifests_have_no_changes() {
+        let old = manifest(r#"{ "algorithm": "sha512", "files": { "a.txt": "aa", "b.txt": { "sha256": "bb", "size": 1 } } }"#);
+        let new = manifest(r#"{ "files": { "a.txt": "sha512:AA", "b.txt": "bb" } }"#);
+
+        assert!(diff_manifests(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn entries_are_added_removed_or_changed() {
+        let diff    = sample_diff();
+        let changes = |kind: ChangeKind| diff.with_change(kind).map(|entry| entry.path.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(changes(ChangeKind::Added), ["d.txt"]);
+        assert_eq!(changes(ChangeKind::Removed), ["b.txt"]);
+        assert_eq!(changes(ChangeKind::Changed), ["c.txt"]);
+        assert_eq!(diff.changes[1].differences, ["hash: sha256:cc -> sha256:ce", "size: 1 -> 2"]);
+    }
+
+    #[test]
+    fn text_diff_lists_each_change() {
+        assert_eq!(
+            render_diff(&sample_diff(), OutputFormat::Text),
+            "Removed: b.txt\nChanged: c.txt\n  hash: sha256:cc -> sha256:ce\n  size: 1 -> 2\nAdded:   d.txt\n"
+        );
+    }
+
+    #[test]
+    fn json_diff_lists_each_change_with_both_entries() {
+        let rendered: serde_json::Value = serde_json::from_str(&render_diff(&sample_diff(), OutputFormat::Json)).unwrap();
+
+        assert_eq!(
+            rendered,
+            serde_json::json!({
+                "identical": false,
+                "changes": [
+                    { "path": "b.txt", "change": "removed", "old": "sha256:bb", "new": null, "differences": [] },
+                    {
+                        "path": "c.txt",
+                        "change": "changed",
+                        "old": { "sha256": "cc", "size": 1 },
+                        "new": { "sha256": "ce", "size": 2 },
+                        "differences": ["hash: sha256:cc -> sha256:ce", "size: 1 -> 2"]
+                    },
+                    { "path": "d.txt", "change": "added", "old": null, "new": "sha256:dd", "differences": [] }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn json_diff_of_identical_manifests() {
+        let old = manifest(r#"{ "files": { "a.txt": "aa" } }"#);
+
+        assert_eq!(render_diff(&diff_manifests(&old, &old), OutputFormat::Json), "{\n  \"identical\": true,\n  \"changes\": []\n}\n");
+    }
+}
@@ -0,0 +1,240 @@
+use crate::archive::read_root_files;
+use crate::error::{Error, Result};
+use crate::manifest::{parse_manifest, Manifest, ManifestFormat};
+use crate::path::normalize_manifest_path;
+use crate::signature::{check_detached_signature, signature_path, TrustPolicy};
+use crate::verify::VerifyOptions;
+use ed25519_dalek::VerifyingKey;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The names a manifest shipped inside the directory or archive it describes is looked for under,
+/// at its root.
+pub const EMBEDDED_MANIFEST_NAMES: [&str; 6] = ["MANIFEST.json", "MANIFEST.yaml", "MANIFEST.yml", "MANIFEST.toml", ".manifest", "SHA256SUMS"];
+
+/// A manifest found at the root of the directory or archive it describes, with its detached
+/// signature if it has one.
+#[derive(Debug, Clone)]
+pub struct EmbeddedManifest {
+    /// The path of the manifest below the directory or archive, such as `bundle.zip/MANIFEST.json`,
+    /// used to detect its format and in messages.
+    pub path: PathBuf,
+    /// The name of the manifest at the root.
+    pub name: String,
+    /// The raw bytes of the manifest.
+    pub contents: Vec<u8>,
+    /// The raw bytes of its `.sig` file, if there is one.
+    pub signature: Option<Vec<u8>>,
+}
+
+impl EmbeddedManifest {
+    /// Parses the manifest, checking its embedded signature like `read_manifest_as`.
+    ///
+    /// Args:
+    /// - `format`: The format the manifest is written in, `None` to detect it.
+    /// - `policy`: The trusted keys and whether a signature is required.
+    ///
+    /// Returns:
+    /// - `Result<Manifest>`: The parsed manifest or an error if it isn't UTF-8, or parsing,
+    ///   validation or the signature check failed.
+    pub fn read(&self, format: Option<ManifestFormat>, policy: &TrustPolicy) -> Result<Manifest> {
+        let contents = std::str::from_utf8(&self.contents)
+            .map_err(|error| Error::manifest(&self.path, format!("cannot open manifest: {}", error)))?;
+
+        parse_manifest(&self.path, contents, format, policy)
+    }
+
+    /// Checks the manifest's detached `.sig` signature against an Ed25519 public key.
+    ///
+    /// Args:
+    /// - `verifying_key`: The public key the manifest must be signed with.
+    ///
+    /// Returns:
+    /// - `Result<()>`: Ok if the signature is valid, or an error if it is missing, malformed or
+    ///   does not match the manifest.
+    pub fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<()> {
+        let signature_file = signature_path(&self.path);
+        let signature      = self
+            .signature
+            .as_ref()
+            .ok_or_else(|| Error::signature(&signature_file, "cannot read signature: the bundle has no signature next to its manifest"))?;
+
+        check_detached_signature(&self.contents, &signature_file, &String::from_utf8_lossy(signature), verifying_key)
+    }
+
+    /// Leaves the manifest and its signature out of verification: drops any entries the manifest
+    /// has for them, and excludes them from the unlisted files reported in strict mode.
+    ///
+    /// Args:
+    /// - `manifest`: The parsed manifest.
+    /// - `options`: The verification options, whose exclude globs are extended.
+    pub fn exclude_from(&self, manifest: &mut Manifest, options: &mut VerifyOptions) {
+        let excluded = [self.name.clone(), format!("{}.sig", self.name)];
+
+        manifest.files.retain(|key, _| normalize_manifest_path(key).is_none_or(|path| !excluded.contains(&path)));
+        options.paths.exclude.extend(excluded.iter().map(|name| format!("/{}", name)));
+    }
+}
+
+/// Finds the manifest shipped at the root of a directory or of a tar, tar.gz or zip archive,
+/// under one of `EMBEDDED_MANIFEST_NAMES`. Only regular files count, symlinks are not followed.
+///
+/// Args:
+/// - `path`: The directory or archive.
+///
+/// Returns:
+/// - `Result<EmbeddedManifest>`: The manifest, or an error if there is none, there are several, or
+///   the directory or archive could not be read.
+pub fn find_embedded_manifest(path: &Path) -> Result<EmbeddedManifest> {
+    let names: Vec<String> = EMBEDDED_MANIFEST_NAMES
+        .iter()
+        .flat_map(|name| [name.to_string(), format!("{}.sig", name)])
+        .collect();
+
+    let metadata  = fs::metadata(path).map_err(|source| Error::io(path, source))?;
+    let mut files = if metadata.is_file() { read_root_files(path, &names)? } else { read_directory_files(path, &names)? };
+
+    let found: Vec<&str> = EMBEDDED_MANIFEST_NAMES.into_iter().filter(|name| files.contains_key(*name)).collect();
+    match found.as_slice() {
+        [] => Err(Error::manifest(
+            path,
+            format!("no embedded manifest found, looked for {} at the root", EMBEDDED_MANIFEST_NAMES.join(", ")),
+        )),
+        [name] => Ok(EmbeddedManifest {
+            path: path.join(name),
+            name: name.to_string(),
+            contents: files.remove(*name).unwrap_or_default(),
+            signature: files.remove(&format!("{}.sig", name)),
+        }),
+        several => Err(Error::manifest(
+            path,
+            format!("found several embedded manifests, {}, pass the one to use with --manifest", several.join(", ")),
+        )),
+    }
+}
+
+/// Reads the regular files with the given names at the root of a directory.
+///
+/// Args:
+/// - `directory_path`: The directory.
+/// - `names`: The names of the files to read.
+///
+/// Returns:
+/// - `Result<HashMap<String, Vec<u8>>>`: The contents of the files that were found, by name, or an
+///   error if one could not be read.
+fn read_directory_files(directory_path: &Path, names: &[String]) -> Result<HashMap<String, Vec<u8>>> {
+    let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+
+    for name in names {
+        let file_path = directory_path.join(name);
+        match fs::symlink_metadata(&file_path) {
+            Ok(metadata) if metadata.is_file() => {
+                let contents = fs::read(&file_path).map_err(|source| Error::io(&file_path, source))?;
+                files.insert(name.clone(), contents);
+            }
+            Ok(_) => {}
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(Error::io(&file_path, error)),
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::FileEntry;
+    use crate::report::FileStatus;
+    use crate::verify::verify_directory;
+    use indexmap::IndexMap;
+
+    const SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    /// Writes the files into a new directory.
+    fn directory_with(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let directory = tempfile::tempdir().unwrap();
+        for (name, contents) in files {
+            fs::write(directory.path().join(name), contents).unwrap();
+        }
+        directory
+    }
+
+    /// Returns why no single embedded manifest was found.
+    fn discovery_error(path: &Path) -> String {
+        match find_embedded_manifest(path) {
+            Err(Error::Manifest { reason, .. }) => reason,
+            other => panic!("expected a manifest error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn the_manifest_at_the_root_is_found_with_its_signature() {
+        let directory = directory_with(&[("SHA256SUMS", "sums"), ("SHA256SUMS.sig", "signature"), ("a.txt", "a")]);
+
+        let embedded = find_embedded_manifest(directory.path()).unwrap();
+        assert_eq!((embedded.name.as_str(), embedded.path.clone()), ("SHA256SUMS", directory.path().join("SHA256SUMS")));
+        assert_eq!((embedded.contents, embedded.signature), (b"sums".to_vec(), Some(b"signature".to_vec())));
+    }
+
+    #[test]
+    fn manifests_are_found_at_the_root_of_archives() {
+        let directory    = tempfile::tempdir().unwrap();
+        let archive_path = directory.path().join("bundle.tar");
+
+        let mut builder = tar::Builder::new(fs::File::create(&archive_path).unwrap());
+        for (name, contents) in [("a.txt", "a"), ("MANIFEST.toml", "toml")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, contents.as_bytes()).unwrap();
+        }
+        builder.finish().unwrap();
+
+        let embedded = find_embedded_manifest(&archive_path).unwrap();
+        assert_eq!((embedded.path, embedded.contents, embedded.signature), (archive_path.join("MANIFEST.toml"), b"toml".to_vec(), None));
+    }
+
+    #[test]
+    fn several_manifests_are_listed_in_discovery_order() {
+        let directory = directory_with(&[("SHA256SUMS", ""), (".manifest", ""), ("MANIFEST.json", "")]);
+
+        assert_eq!(
+            discovery_error(directory.path()),
+            "found several embedded manifests, MANIFEST.json, .manifest, SHA256SUMS, pass the one to use with --manifest"
+        );
+    }
+
+    #[test]
+    fn only_regular_files_count_as_manifests() {
+        let directory = directory_with(&[("a.txt", "a")]);
+        fs::create_dir(directory.path().join("MANIFEST.json")).unwrap();
+
+        assert!(discovery_error(directory.path()).starts_with("no embedded manifest found, looked for MANIFEST.json, MANIFEST.yaml"));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(directory.path().join("a.txt"), directory.path().join("SHA256SUMS")).unwrap();
+            assert!(discovery_error(directory.path()).starts_with("no embedded manifest found"));
+        }
+    }
+
+    #[test]
+    fn the_manifest_and_its_signature_are_left_out_of_the_check() {
+        let directory = directory_with(&[("a.txt", ""), ("MANIFEST.json", "{}"), ("MANIFEST.json.sig", "signature")]);
+        let embedded  = find_embedded_manifest(directory.path()).unwrap();
+
+        let json         = format!(r#"{{"files": {{"a.txt": "{0}", "./MANIFEST.json": "{0}"}}}}"#, SHA256);
+        let mut manifest = parse_manifest(&embedded.path, &json, None, &TrustPolicy::default()).unwrap();
+        let mut options  = VerifyOptions { strict: true, ..VerifyOptions::default() };
+        embedded.exclude_from(&mut manifest, &mut options);
+
+        assert_eq!(manifest.files, IndexMap::from([("a.txt".to_string(), FileEntry::new(SHA256))]));
+
+        let report = verify_directory(directory.path(), &manifest, &options).unwrap();
+        let statuses: Vec<(&str, FileStatus)> = report.files.iter().map(|file| (file.path.as_str(), file.status)).collect();
+        assert_eq!(statuses, [("a.txt", FileStatus::Matched)]);
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Errors that stop a manifest from being read, written, signed or checked.
+///
+/// Per-file problems found during verification, such as mismatched or missing files, are not
+/// errors; they are recorded in the `VerificationReport` instead.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io { path: PathBuf, source: io::Error },
+    /// Walking a directory failed.
+    Walk(walkdir::Error),
+    /// The manifest file could not be opened or is invalid.
+    Manifest { path: PathBuf, reason: String },
+    /// The manifest is not well-formed JSON, YAML, TOML or checksum list, or doesn't match the
+    /// manifest schema.
+    Parse { path: PathBuf, line: usize, column: usize, key: Option<String>, message: String },
+    /// A manifest entry has an invalid path or hash.
+    InvalidEntry { path: PathBuf, key: String, reason: String },
+    /// A path escapes the directory or is a symlink the symlink policy rejects.
+    UnsafePath { path: PathBuf, reason: String },
+    /// The manifest's signature is missing, malformed or doesn't validate.
+    Signature { path: PathBuf, reason: String },
+    /// A signing or verifying key is malformed.
+    Key { path: PathBuf, reason: String },
+    /// An include, exclude or ignore pattern is invalid, with the file it came from, if any.
+    Pattern { path: Option<PathBuf>, reason: String },
+    /// An archive is not a supported format or is corrupt.
+    Archive { path: PathBuf, reason: String },
+}
+
+/// A `Result` whose error is the crate's `Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Builds an I/O error for the given path.
+    ///
+    /// Args:
+    /// - `path`: The file or directory the operation failed on.
+    /// - `source`: The underlying I/O error.
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Error::Io { path: path.to_path_buf(), source }
+    }
+
+    /// Builds a manifest error for the given manifest path.
+    pub(crate) fn manifest(path: &Path, reason: impl Into<String>) -> Self {
+        Error::Manifest { path: path.to_path_buf(), reason: reason.into() }
+    }
+
+    /// Builds an unsafe path error for the given path.
+    pub(crate) fn unsafe_path(path: &Path, reason: impl Into<String>) -> Self {
+        Error::UnsafePath { path: path.to_path_buf(), reason: reason.into() }
+    }
+
+    /// Builds a signature error for the given manifest path.
+    pub(crate) fn signature(path: &Path, reason: impl Into<String>) -> Self {
+        Error::Signature { path: path.to_path_buf(), reason: reason.into() }
+    }
+
+    /// Builds a key error for the given key path.
+    pub(crate) fn key(path: &Path, reason: impl Into<String>) -> Self {
+        Error::Key { path: path.to_path_buf(), reason: reason.into() }
+    }
+
+    /// Builds a pattern error, for a pattern read from the given file if there is one.
+    pub(crate) fn pattern(path: Option<&Path>, reason: impl Into<String>) -> Self {
+        Error::Pattern { path: path.map(Path::to_path_buf), reason: reason.into() }
+    }
+
+    /// Builds an archive error for the given archive path.
+    pub(crate) fn archive(path: &Path, reason: impl Into<String>) -> Self {
+        Error::Archive { path: path.to_path_buf(), reason: reason.into() }
+    }
+
+    /// Returns `true` if the error concerns the manifest or its trust rather than the file system.
+    pub fn is_manifest_error(&self) -> bool {
+        matches!(
+            self,
+            Error::Manifest { .. }
+                | Error::Parse { .. }
+                | Error::InvalidEntry { .. }
+                | Error::Signature { .. }
+                | Error::Key { .. }
+                | Error::Pattern { .. }
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source }        => write!(f, "{}: {}", path.display(), source),
+            Error::Walk(source)               => write!(f, "{}", source),
+            Error::Manifest { path, reason }  => write!(f, "{}: {}", path.display(), reason),
+            Error::Parse { path, line, column, key: Some(key), message } => {
+                write!(f, "{}:{}:{}: at `{}`: {}", path.display(), line, column, key, message)
+            }
+            Error::Parse { path, line, column, key: None, message } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)
+            }
+            Error::InvalidEntry { path, key, reason } => {
+                write!(f, "{}: entry \"{}\": {}", path.display(), key, reason)
+            }
+            Error::UnsafePath { path, reason } => write!(f, "{}: unsafe path: {}", path.display(), reason),
+            Error::Signature { path, reason } => write!(f, "{}: invalid signature: {}", path.display(), reason),
+            Error::Key { path, reason }       => write!(f, "{}: invalid key: {}", path.display(), reason),
+            Error::Pattern { path: Some(path), reason } => write!(f, "{}: {}", path.display(), reason),
+            Error::Pattern { path: None, reason } => write!(f, "{}", reason),
+            Error::Archive { path, reason }   => write!(f, "{}: invalid archive: {}", path.display(), reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Walk(source)      => Some(source),
+            _                        => None,
+        }
+    }
+}
+
+impl From<walkdir::Error> for Error {
+    fn from(source: walkdir::Error) -> Self {
+        Error::Walk(source)
+    }
+}
@@ -0,0 +1,344 @@
+use crate::error::{Error, Result};
+use crate::hash::hash_bytes;
+use crate::manifest::{FileEntry, Manifest};
+use crate::path::{key_within, normalize_manifest_path};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The name of the file at the directory root that lists paths to leave out, with gitignore semantics.
+pub const IGNORE_FILE_NAME: &str = ".manifestignore";
+
+/// Include and exclude globs deciding which paths in the directory are recorded by generation and
+/// scanned for unlisted files in strict mode.
+///
+/// Globs match forward-slash paths relative to the directory at any depth, so `__pycache__/**`
+/// matches every `__pycache__` directory's contents, unless they start with `/`, which anchors them
+/// to the directory root. `*` and `?` stay within one path component, only `**` crosses
+/// directories. A path matches a glob if it, or any directory above it, does.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathRules {
+    /// Only paths matching at least one of these globs are considered. Empty means every path.
+    pub include: Vec<String>,
+    /// Paths matching any of these globs are left out, even if they are included.
+    pub exclude: Vec<String>,
+}
+
+impl PathRules {
+    /// Leaves a manifest stored inside the directory, and its detached `.sig` signature, out of the
+    /// paths, so writing the manifest doesn't change the files it describes.
+    ///
+    /// Args:
+    /// - `directory_path`: Path to the directory the rules apply to.
+    /// - `manifest_path`: Path the manifest is read from or written to, which needn't exist yet.
+    pub fn exclude_manifest(&mut self, directory_path: &Path, manifest_path: &Path) {
+        if let Some(key) = key_within(directory_path, manifest_path) {
+            self.exclude.push(format!("/{}", globset::escape(&key)));
+            self.exclude.push(format!("/{}", globset::escape(&format!("{}.sig", key))));
+        }
+    }
+}
+
+/// Path rules compiled for one directory, together with its `.manifestignore` file.
+pub(crate) struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    ignore_file: Option<Gitignore>,
+}
+
+impl PathFilter {
+    /// Compiles the rules and reads the directory's `.manifestignore` file, if there is one.
+    ///
+    /// Args:
+    /// - `directory_path`: Path to the directory the rules apply to.
+    /// - `rules`: The include and exclude globs.
+    /// - `ignore`: Further exclude globs, such as the `ignore` list of a manifest.
+    ///
+    /// Returns:
+    /// - `Result<PathFilter>`: The compiled filter, or an error if a glob is invalid or the ignore
+    ///   file could not be read.
+    pub(crate) fn new(directory_path: &Path, rules: &PathRules, ignore: &[String]) -> Result<Self> {
+        let ignore_path = directory_path.join(IGNORE_FILE_NAME);
+        let contents    = read_ignore_file(&ignore_path)?
+            .map(|bytes| String::from_utf8(bytes).map_err(|error| Error::io(&ignore_path, io::Error::new(io::ErrorKind::InvalidData, error))))
+            .transpose()?;
+
+        PathFilter::with_ignore_file(rules, ignore, &ignore_path, contents.as_deref())
+    }
+
+    /// Compiles the rules for the unlisted-file scan of strict verification. The directory being
+    /// verified isn't trusted, so its `.manifestignore` file is only read if the manifest lists it,
+    /// and only honored if its hash matches, see `listed_ignore_file`.
+    ///
+    /// Args:
+    /// - `directory_path`: Path to the directory being verified.
+    /// - `rules`: The include and exclude globs.
+    /// - `manifest`: The manifest the directory is verified against, whose `ignore` list applies.
+    ///
+    /// Returns:
+    /// - `Result<PathFilter>`: The compiled filter, or an error if a glob or ignore pattern is
+    ///   invalid.
+    pub(crate) fn for_verification(directory_path: &Path, rules: &PathRules, manifest: &Manifest) -> Result<Self> {
+        let ignore_path = directory_path.join(IGNORE_FILE_NAME);
+
+        // A listed ignore file that can't be read is reported by its own entry, and not honored.
+        let contents = match ignore_file_entry(manifest) {
+            Some(_) => read_ignore_file(&ignore_path).ok().flatten(),
+            None => None,
+        };
+
+        PathFilter::with_ignore_file(rules, &manifest.ignore, &ignore_path, listed_ignore_file(manifest, contents.as_deref()).as_deref())
+    }
+
+    /// Compiles the rules together with the contents of an ignore file that was already read, such
+    /// as one stored inside an archive.
+    ///
+    /// Args:
+    /// - `rules`: The include and exclude globs.
+    /// - `ignore`: Further exclude globs, such as the `ignore` list of a manifest.
+    /// - `ignore_path`: The path of the ignore file, whose parent its patterns are relative to.
+    /// - `contents`: The contents of the ignore file, `None` if there is none.
+    ///
+    /// Returns:
+    /// - `Result<PathFilter>`: The compiled filter, or an error if a glob or ignore pattern is invalid.
+    pub(crate) fn with_ignore_file(rules: &PathRules, ignore: &[String], ignore_path: &Path, contents: Option<&str>) -> Result<Self> {
+        let include     = if rules.include.is_empty() { None } else { Some(build_glob_set(&rules.include)?) };
+        let exclude     = build_glob_set(rules.exclude.iter().chain(ignore))?;
+        let ignore_file = contents.map(|contents| parse_ignore_file(ignore_path, contents)).transpose()?;
+
+        Ok(PathFilter { include, exclude, ignore_file })
+    }
+
+    /// Returns `true` if the path is left out by the include and exclude globs or the ignore file.
+    ///
+    /// Args:
+    /// - `key`: Forward-slash path relative to the directory.
+    /// - `is_dir`: Whether the path is a directory, for ignore file patterns ending in `/`.
+    pub(crate) fn is_excluded(&self, key: &str, is_dir: bool) -> bool {
+        // The path itself and every directory above it, as in `a`, `a/b`, `a/b/c`.
+        let candidates: Vec<&str> = key.match_indices('/').map(|(index, _)| &key[..index]).chain([key]).collect();
+
+        if let Some(include) = &self.include {
+            if !candidates.iter().any(|candidate| include.is_match(candidate)) {
+                return true;
+            }
+        }
+        if candidates.iter().any(|candidate| self.exclude.is_match(candidate)) {
+            return true;
+        }
+
+        // The ignore file never leaves itself out, so it can't hide that it is there.
+        key != IGNORE_FILE_NAME
+            && self.ignore_file.as_ref().is_some_and(|ignore_file| ignore_file.matched_path_or_any_parents(key, is_dir).is_ignore())
+    }
+}
+
+/// Returns the contents of a verified directory's `.manifestignore` file if the manifest vouches
+/// for them: it lists the file, and the contents match the recorded hash. Otherwise the file could
+/// hide any unlisted file shipped next to it, so it is left out and reported like any other file.
+/// The raw bytes are hashed, and only decoded once they match.
+///
+/// Args:
+/// - `manifest`: The manifest the directory is verified against.
+/// - `contents`: The raw contents of the ignore file, `None` if there is none.
+///
+/// Returns:
+/// - `Option<String>`: The decoded contents if the manifest lists them, or `None`.
+pub(crate) fn listed_ignore_file(manifest: &Manifest, contents: Option<&[u8]>) -> Option<String> {
+    let contents      = contents?;
+    let entry         = ignore_file_entry(manifest)?;
+    let expected_hash = entry.hash.as_ref()?;
+    let actual_hash   = hash_bytes(contents, manifest.algorithm_for(entry));
+
+    actual_hash
+        .eq_ignore_ascii_case(expected_hash)
+        .then(|| String::from_utf8_lossy(contents).into_owned())
+}
+
+/// Returns the manifest's entry for the `.manifestignore` file at the root, if it lists one.
+fn ignore_file_entry(manifest: &Manifest) -> Option<&FileEntry> {
+    manifest
+        .files
+        .iter()
+        .find(|(key, _)| normalize_manifest_path(key).as_deref() == Some(IGNORE_FILE_NAME))
+        .map(|(_, entry)| entry)
+}
+
+/// Reads an ignore file if it is a regular file. Anything else, such as a symlink or a FIFO, is
+/// never opened, as it could point outside the directory or block the read.
+///
+/// Args:
+/// - `ignore_path`: The path of the ignore file.
+///
+/// Returns:
+/// - `Result<Option<Vec<u8>>>`: The raw contents, `None` if there is no such regular file, or an
+///   error if it could not be read.
+fn read_ignore_file(ignore_path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::symlink_metadata(ignore_path) {
+        Ok(metadata) if metadata.is_file() => {}
+        Ok(_) => return Ok(None),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(Error::io(ignore_path, error)),
+    }
+
+    let mut file = File::open(ignore_path).map_err(|source| Error::io(ignore_path, source))?;
+
+    // The path may have been replaced since it was checked.
+    if !file.metadata().map_err(|source| Error::io(ignore_path, source))?.is_file() {
+        return Ok(None);
+    }
+
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).map_err(|source| Error::io(ignore_path, source))?;
+
+    Ok(Some(contents))
+}
+
+/// Checks that a glob is valid, without compiling it into a filter.
+///
+/// Args:
+/// - `pattern`: The glob to check.
+///
+/// Returns:
+/// - `Result<(), String>`: Nothing if the glob is valid, or why it isn't.
+pub fn validate_glob(pattern: &str) -> std::result::Result<(), String> {
+    compile_glob(pattern).map(|_| ()).map_err(|error| error.kind().to_string())
+}
+
+/// Compiles a glob so it matches at any depth, unless it is anchored to the root with a leading `/`.
+/// As in `.gitignore`, `*` and `?` never match a `/`, only `**` crosses directories.
+fn compile_glob(pattern: &str) -> std::result::Result<Glob, globset::Error> {
+    let glob = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None => format!("**/{}", pattern),
+    };
+
+    GlobBuilder::new(&glob).literal_separator(true).build()
+}
+
+/// Describes why a glob is invalid, naming the glob.
+fn pattern_reason(pattern: &str, reason: impl std::fmt::Display) -> String {
+    format!("invalid pattern `{}`: {}", pattern, reason)
+}
+
+/// Compiles globs into a single set.
+fn build_glob_set<'a>(patterns: impl IntoIterator<Item = &'a String>) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        let glob = compile_glob(pattern).map_err(|error| Error::pattern(None, pattern_reason(pattern, error.kind())))?;
+        builder.add(glob);
+    }
+
+    builder.build().map_err(|error| Error::pattern(None, error.to_string()))
+}
+
+/// Compiles the patterns of a `.manifestignore` file.
+///
+/// Args:
+/// - `ignore_path`: The path of the ignore file, used as the root of its patterns and in errors.
+/// - `contents`: The contents of the ignore file.
+///
+/// Returns:
+/// - `Result<Gitignore>`: The compiled ignore file, or an error if it has an invalid pattern.
+fn parse_ignore_file(ignore_path: &Path, contents: &str) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(ignore_path.parent().unwrap_or(ignore_path));
+    for line in contents.lines() {
+        builder.add_line(None, line).map_err(|error| match error {
+            ignore::Error::Glob { err, .. } => Error::pattern(Some(ignore_path), pattern_reason(line, err)),
+            other => Error::pattern(Some(ignore_path), other.to_string()),
+        })?;
+    }
+
+    builder.build().map_err(|error| Error::pattern(Some(ignore_path), error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashAlgorithm;
+    use crate::manifest::ManifestFormat;
+    use indexmap::IndexMap;
+
+    /// Returns a manifest listing `a.txt` and, if given, an ignore file with these contents.
+    fn manifest_listing(ignore_file: Option<&[u8]>) -> Manifest {
+        let mut files: IndexMap<String, FileEntry> = IndexMap::new();
+        files.insert("a.txt".to_string(), FileEntry::new(hash_bytes(b"a", HashAlgorithm::Sha256)));
+        if let Some(contents) = ignore_file {
+            files.insert(IGNORE_FILE_NAME.to_string(), FileEntry::new(hash_bytes(contents, HashAlgorithm::Sha256)));
+        }
+
+        Manifest {
+            algorithm: None,
+            files,
+            ignore: Vec::new(),
+            signature: None,
+            extra: IndexMap::new(),
+            format: ManifestFormat::Json,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Returns whether strict verification of the directory leaves `backdoor` out.
+    fn hides_backdoor(directory_path: &Path, manifest: &Manifest) -> bool {
+        PathFilter::for_verification(directory_path, &PathRules::default(), manifest)
+            .unwrap()
+            .is_excluded("backdoor", false)
+    }
+
+    #[test]
+    fn ignore_file_applies_only_if_listed_with_a_matching_hash() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(directory.path().join(IGNORE_FILE_NAME), "backdoor\n").unwrap();
+
+        assert!(hides_backdoor(directory.path(), &manifest_listing(Some(b"backdoor\n"))));
+        assert!(!hides_backdoor(directory.path(), &manifest_listing(Some(b"other\n"))));
+        assert!(!hides_backdoor(directory.path(), &manifest_listing(None)));
+    }
+
+    #[test]
+    fn ignore_file_never_excludes_itself() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(directory.path().join(IGNORE_FILE_NAME), "*\n").unwrap();
+
+        let filter = PathFilter::for_verification(directory.path(), &PathRules::default(), &manifest_listing(Some(b"*\n"))).unwrap();
+        assert!(filter.is_excluded("backdoor", false));
+        assert!(!filter.is_excluded(IGNORE_FILE_NAME, false));
+    }
+
+    #[test]
+    fn ignore_file_that_is_not_utf8_is_hashed_before_it_is_decoded() {
+        let directory = tempfile::tempdir().unwrap();
+        let contents  = b"backdoor\n\xff\xfe\n";
+        fs::write(directory.path().join(IGNORE_FILE_NAME), contents).unwrap();
+
+        assert!(!hides_backdoor(directory.path(), &manifest_listing(None)));
+        assert!(hides_backdoor(directory.path(), &manifest_listing(Some(contents))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ignore_file_that_is_a_symlink_is_not_followed() {
+        let directory = tempfile::tempdir().unwrap();
+        let outside   = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("ignore"), "backdoor\n").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("ignore"), directory.path().join(IGNORE_FILE_NAME)).unwrap();
+
+        assert!(!hides_backdoor(directory.path(), &manifest_listing(Some(b"backdoor\n"))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ignore_file_that_is_a_fifo_is_not_opened() {
+        let directory = tempfile::tempdir().unwrap();
+        let status    = std::process::Command::new("mkfifo").arg(directory.path().join(IGNORE_FILE_NAME)).status().unwrap();
+        assert!(status.success());
+
+        // Opening the FIFO would block until a writer shows up, so these return only if it isn't.
+        assert!(!hides_backdoor(directory.path(), &manifest_listing(None)));
+        assert!(!hides_backdoor(directory.path(), &manifest_listing(Some(b"backdoor\n"))));
+        assert!(PathFilter::new(directory.path(), &PathRules::default(), &[]).is_ok());
+    }
+}
@@ -0,0 +1,302 @@
+use crate::error::{Error, Result};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use sha3::Sha3_256;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Regular files at least this large are memory-mapped and hashed in place instead of being read
+/// into a buffer.
+pub const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// The smallest buffer files are read with.
+const MIN_BUFFER_SIZE: usize = 8 * 1024;
+
+/// The largest buffer files are read with.
+const MAX_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// The buffer size for readers of unknown length, such as archive members.
+const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The hash algorithms a manifest entry can be recorded with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum HashAlgorithm {
+    #[default]
+    #[serde(rename = "sha256")]
+    Sha256,
+    #[serde(rename = "sha512")]
+    Sha512,
+    #[serde(rename = "sha1")]
+    Sha1,
+    #[serde(rename = "sha3-256")]
+    Sha3_256,
+    #[serde(rename = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// All supported algorithms, in the order they are listed in help texts.
+    pub const ALL: [HashAlgorithm; 5] = [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Sha1,
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Blake3,
+    ];
+
+    /// Returns the name used for this algorithm in manifests and digest prefixes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256   => "sha256",
+            HashAlgorithm::Sha512   => "sha512",
+            HashAlgorithm::Sha1     => "sha1",
+            HashAlgorithm::Sha3_256 => "sha3-256",
+            HashAlgorithm::Blake3   => "blake3",
+        }
+    }
+
+    /// Returns the number of hexadecimal characters in a digest of this algorithm.
+    pub fn hex_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256   => 64,
+            HashAlgorithm::Sha512   => 128,
+            HashAlgorithm::Sha1     => 40,
+            HashAlgorithm::Sha3_256 => 64,
+            HashAlgorithm::Blake3   => 64,
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        HashAlgorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.as_str().eq_ignore_ascii_case(value))
+            .ok_or_else(|| format!("unknown hash algorithm: {}", value))
+    }
+}
+
+/// A running hash computation for one of the supported algorithms.
+enum Hasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Sha1(Sha1),
+    Sha3_256(Sha3_256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    /// Creates a hasher for the given algorithm.
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256   => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512   => Hasher::Sha512(Sha512::new()),
+            HashAlgorithm::Sha1     => Hasher::Sha1(Sha1::new()),
+            HashAlgorithm::Sha3_256 => Hasher::Sha3_256(Sha3_256::new()),
+            HashAlgorithm::Blake3   => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    /// Feeds a chunk of data into the hash.
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher)   => hasher.update(data),
+            Hasher::Sha512(hasher)   => hasher.update(data),
+            Hasher::Sha1(hasher)     => hasher.update(data),
+            Hasher::Sha3_256(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher)   => { hasher.update(data); }
+        }
+    }
+
+    /// Consumes the hasher and returns the lowercase hexadecimal digest.
+    fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Sha256(hasher)   => format!("{:x}", hasher.finalize()),
+            Hasher::Sha512(hasher)   => format!("{:x}", hasher.finalize()),
+            Hasher::Sha1(hasher)     => format!("{:x}", hasher.finalize()),
+            Hasher::Sha3_256(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Blake3(hasher)   => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Calculates the hash of a file at a given path with the given algorithm.
+///
+/// Files of at least `MMAP_THRESHOLD` bytes are memory-mapped and hashed in place, falling back to
+/// reading them if the file system doesn't support mapping. Smaller files are read with a buffer
+/// sized to the file. Special files such as FIFOs and devices are refused before they are opened,
+/// as opening a FIFO without a writer blocks forever.
+///
+/// Args:
+/// - `path`: A path reference to the file to hash.
+/// - `algorithm`: The hash algorithm to use.
+///
+/// Returns:
+/// - `Result<String>`: The hexadecimal representation of the file hash, or an error naming the file.
+pub fn hash_file<P: AsRef<Path>>(path: P, algorithm: HashAlgorithm) -> Result<String> {
+    let path = path.as_ref();
+    let not_regular = || Error::io(path, io::Error::new(io::ErrorKind::InvalidInput, "not a regular file"));
+
+    if !fs::metadata(path).map_err(|source| Error::io(path, source))?.is_file() {
+        return Err(not_regular());
+    }
+
+    let file     = File::open(path).map_err(|source| Error::io(path, source))?;
+    let metadata = file.metadata().map_err(|source| Error::io(path, source))?;
+
+    // The path may have been replaced since it was checked.
+    if !metadata.is_file() {
+        return Err(not_regular());
+    }
+
+    if metadata.len() >= MMAP_THRESHOLD {
+        // SAFETY: the map is only read, and dropped before returning. As with any mapped file, the
+        // process is killed by SIGBUS if another process truncates the file while it is hashed.
+        if let Ok(map) = unsafe { Mmap::map(&file) } {
+            #[cfg(unix)]
+            let _ = map.advise(memmap2::Advice::Sequential);
+
+            return Ok(hash_bytes(&map, algorithm));
+        }
+    }
+
+    hash_buffered(file, algorithm, buffer_size(metadata.len())).map_err(|source| Error::io(path, source))
+}
+
+/// Returns the buffer size a file of the given length is read with: the whole file if it is small,
+/// bounded by `MIN_BUFFER_SIZE` and `MAX_BUFFER_SIZE`.
+///
+/// Args:
+/// - `len`: The length of the file in bytes.
+fn buffer_size(len: u64) -> usize {
+    usize::try_from(len).unwrap_or(MAX_BUFFER_SIZE).clamp(MIN_BUFFER_SIZE, MAX_BUFFER_SIZE)
+}
+
+/// Calculates the hash of everything a reader yields with the given algorithm, such as a file
+/// streamed out of an archive.
+///
+/// Args:
+/// - `reader`: The reader to hash until its end.
+/// - `algorithm`: The hash algorithm to use.
+///
+/// Returns:
+/// - `io::Result<String>`: The hexadecimal representation of the hash, or the error reading failed with.
+pub fn hash_reader<R: Read>(reader: R, algorithm: HashAlgorithm) -> io::Result<String> {
+    hash_buffered(reader, algorithm, STREAM_BUFFER_SIZE)
+}
+
+/// Calculates the hash of everything a reader yields, reading it in chunks of the given size.
+///
+/// Args:
+/// - `reader`: The reader to hash until its end.
+/// - `algorithm`: The hash algorithm to use.
+/// - `buffer_size`: The size of the chunks read at a time.
+///
+/// Returns:
+/// - `io::Result<String>`: The hexadecimal representation of the hash, or the error reading failed with.
+fn hash_buffered<R: Read>(mut reader: R, algorithm: HashAlgorithm, buffer_size: usize) -> io::Result<String> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0; buffer_size];
+
+    // Read the content in chunks and update the hash.
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0) => break, // End of the content reached.
+            Ok(count) => count,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        hasher.update(&buffer[..count]);
+    }
+
+    // Return the final hash in hexadecimal format.
+    Ok(hasher.finalize_hex())
+}
+
+/// Calculates the hash of a byte slice with the given algorithm.
+///
+/// Args:
+/// - `data`: The bytes to hash.
+/// - `algorithm`: The hash algorithm to use.
+///
+/// Returns:
+/// - `String`: The hexadecimal representation of the hash.
+pub fn hash_bytes(data: &[u8], algorithm: HashAlgorithm) -> String {
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update(data);
+    hasher.finalize_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `len` bytes of a repeating pattern into a new file, returning the directory with the
+    /// file's path and contents.
+    fn file_of_len(len: u64) -> (tempfile::TempDir, std::path::PathBuf, Vec<u8>) {
+        let directory = tempfile::tempdir().unwrap();
+        let file_path = directory.path().join("data.bin");
+        let contents: Vec<u8> = (0..len).map(|index| (index % 251) as u8).collect();
+        fs::write(&file_path, &contents).unwrap();
+
+        (directory, file_path, contents)
+    }
+
+    #[test]
+    fn mapped_and_buffered_files_hash_like_their_contents() {
+        for len in [0, 1, MMAP_THRESHOLD - 1, MMAP_THRESHOLD] {
+            let (_directory, file_path, contents) = file_of_len(len);
+
+            let expected = hash_reader(contents.as_slice(), HashAlgorithm::Sha256).unwrap();
+            assert_eq!(hash_file(&file_path, HashAlgorithm::Sha256).unwrap(), expected, "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn buffers_are_sized_to_the_file_within_bounds() {
+        assert_eq!(buffer_size(0), MIN_BUFFER_SIZE);
+        assert_eq!(buffer_size(100 * 1024), 100 * 1024);
+        assert_eq!(buffer_size(MMAP_THRESHOLD), MAX_BUFFER_SIZE);
+        assert_eq!(buffer_size(u64::MAX), MAX_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn directories_are_not_hashed() {
+        let directory = tempfile::tempdir().unwrap();
+        let error     = hash_file(directory.path(), HashAlgorithm::Sha256).unwrap_err();
+
+        assert!(error.to_string().contains("not a regular file"), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fifos_are_refused_without_blocking() {
+        use std::sync::mpsc;
+        use std::thread;
+        use std::time::Duration;
+
+        let directory = tempfile::tempdir().unwrap();
+        let fifo_path = directory.path().join("fifo");
+        assert!(std::process::Command::new("mkfifo").arg(&fifo_path).status().unwrap().success());
+
+        // Opening a FIFO without a writer blocks, so a hang fails the test instead of stalling it.
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || sender.send(hash_file(&fifo_path, HashAlgorithm::Sha256).map_err(|error| error.to_string())));
+
+        let result = receiver.recv_timeout(Duration::from_secs(5)).expect("hashing a FIFO blocked");
+        assert!(result.unwrap_err().contains("not a regular file"));
+    }
+}
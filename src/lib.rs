@@ -0,0 +1,37 @@
+//! Verifies files in a directory against a checksum manifest.
+//!
+//! The library exposes the manifest format, the hashing routine and a directory verifier that
+//! returns a `VerificationReport` instead of printing, so it can be embedded in other tools.
+
+mod archive;
+mod checksum;
+mod diff;
+mod embedded;
+mod error;
+mod filter;
+mod hash;
+mod manifest;
+mod metadata;
+mod output;
+mod path;
+mod report;
+mod signature;
+mod update;
+mod verify;
+
+pub use archive::{verify_archive, ArchiveFormat};
+pub use diff::{diff_manifests, ChangeKind, EntryChange, ManifestDiff};
+pub use embedded::{find_embedded_manifest, EmbeddedManifest, EMBEDDED_MANIFEST_NAMES};
+pub use error::{Error, Result};
+pub use filter::{validate_glob, PathRules, IGNORE_FILE_NAME};
+pub use hash::{hash_bytes, hash_file, hash_reader, HashAlgorithm, MMAP_THRESHOLD};
+pub use manifest::{generate_manifest, parse_manifest, read_manifest, read_manifest_as, serialize_manifest, write_manifest, FileEntry, GenerateOptions, Manifest, ManifestFormat};
+pub use metadata::{read_metadata, EntryType, FileMetadata};
+pub use output::{render_diff, render_report, OutputFormat};
+pub use path::{normalize_manifest_path, SymlinkPolicy};
+pub use report::{FileReport, FileStatus, VerificationReport};
+pub use signature::{canonical_signing_bytes, check_detached_signature, check_embedded_signature, embed_manifest_signature, key_id};
+pub use signature::{read_signing_key, read_verifying_key, sign_manifest, signature_path, verify_manifest_signature};
+pub use signature::{ManifestSignature, TrustPolicy};
+pub use update::{update_manifest, UpdateOptions, UpdateSummary};
+pub use verify::{default_jobs, verify_directory, VerifyOptions};
@@ -1,161 +1,1141 @@
-use clap::{App, Arg};
-use serde::Deserialize;
-use serde_json::from_reader;
-use sha2::{Digest, Sha256};
+use clap::{App, Arg, ArgMatches};
+use manifest_checker::{generate_manifest, parse_manifest, read_manifest_as, verify_archive, verify_directory, write_manifest};
+use manifest_checker::{check_detached_signature, embed_manifest_signature, read_verifying_key, sign_manifest};
+use manifest_checker::{Error, FileEntry, FileStatus, Manifest, ManifestFormat, Result, TrustPolicy, VerificationReport};
+use manifest_checker::{default_jobs, render_report, GenerateOptions, HashAlgorithm, OutputFormat, SymlinkPolicy, VerifyOptions};
+use manifest_checker::{diff_manifests, render_diff, validate_glob, ChangeKind, PathRules};
+use manifest_checker::{signature_path, update_manifest, UpdateOptions};
+use manifest_checker::{find_embedded_manifest, serialize_manifest};
+use std::fs;
 use std::process;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-/// Represents the expected structure of the manifest file.
-#[derive(Debug, Deserialize)]
-struct Manifest {
-    files: HashMap<String, String>,
+/// The action selected on the command line.
+enum Command {
+    /// Verify a directory against a manifest.
+    Verify(VerifyArgs),
+    /// Generate a manifest from the files in a directory.
+    Generate(GenerateArgs),
+    /// Sign a manifest with a private key.
+    Sign(SignArgs),
+    /// Compare the entries of two manifests.
+    Diff(DiffArgs),
+    /// Refresh a manifest from its directory in place.
+    Update(UpdateArgs),
+    /// Rewrite a manifest into its canonical form.
+    Canonicalize(CanonicalizeArgs),
+    /// Translate a manifest into another format.
+    Convert(ConvertArgs),
+}
+
+/// Arguments of the verify command.
+struct VerifyArgs {
+    /// The manifest to verify against, `None` to use the one embedded in the directory or archive.
+    manifest_path: Option<PathBuf>,
+    manifest_format: Option<ManifestFormat>,
+    directory_path: PathBuf,
+    public_key_path: Option<PathBuf>,
+    trusted_key_paths: Vec<PathBuf>,
+    require_signature: bool,
+    /// Whether an embedded manifest is used without checking its signature.
+    allow_unsigned: bool,
+    options: VerifyOptions,
+    format: OutputFormat,
+    verbose: bool,
+}
+
+/// Arguments of the generate command.
+struct GenerateArgs {
+    directory_path: PathBuf,
+    output_path: PathBuf,
+    options: GenerateOptions,
+    format: ManifestFormat,
+}
+
+/// Arguments of the sign command.
+struct SignArgs {
+    manifest_path: PathBuf,
+    private_key_path: PathBuf,
+    embed: bool,
+}
+
+/// Arguments of the diff command.
+struct DiffArgs {
+    old_manifest_path: PathBuf,
+    new_manifest_path: PathBuf,
+    manifest_format: Option<ManifestFormat>,
+    format: OutputFormat,
+}
+
+/// Arguments of the update command.
+struct UpdateArgs {
+    manifest_path: PathBuf,
+    manifest_format: Option<ManifestFormat>,
+    directory_path: PathBuf,
+    options: UpdateOptions,
+}
+
+/// Arguments of the canonicalize command.
+struct CanonicalizeArgs {
+    manifest_path: PathBuf,
+    manifest_format: Option<ManifestFormat>,
+    output_path: PathBuf,
+}
+
+/// Arguments of the convert command.
+struct ConvertArgs {
+    manifest_path: PathBuf,
+    manifest_format: Option<ManifestFormat>,
+    output_path: PathBuf,
+    format: ManifestFormat,
+}
+
+/// The documented exit codes of the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitStatus {
+    /// Everything matched, or the command completed.
+    Ok = 0,
+    /// A file's content or metadata didn't match, a path is unsafe, (in strict mode) a file isn't
+    /// listed in the manifest, or two compared manifests differ.
+    Mismatch = 1,
+    /// A file listed in the manifest is missing from the directory.
+    Missing = 2,
+    /// The manifest, its signature or a key is invalid.
+    ManifestError = 3,
+    /// A file or directory could not be read or written, or an archive is corrupt.
+    IoError = 4,
+}
+
+impl ExitStatus {
+    /// Returns the exit status for an error that stopped a command.
+    ///
+    /// Args:
+    /// - `error`: The error that stopped the command.
+    fn from_error(error: &Error) -> Self {
+        if error.is_manifest_error() {
+            ExitStatus::ManifestError
+        } else {
+            ExitStatus::IoError
+        }
+    }
+
+    /// Returns the exit status for a completed verification. Mismatches take precedence over
+    /// missing files, which take precedence over unreadable files.
+    ///
+    /// Args:
+    /// - `report`: The verification report.
+    fn from_report(report: &VerificationReport) -> Self {
+        let has = |status: FileStatus| report.with_status(status).next().is_some();
+
+        let mismatch_statuses = [
+            FileStatus::Mismatched,
+            FileStatus::SizeMismatch,
+            FileStatus::MetadataMismatch,
+            FileStatus::Extra,
+            FileStatus::Unsafe,
+        ];
+
+        if mismatch_statuses.into_iter().any(has) {
+            ExitStatus::Mismatch
+        } else if has(FileStatus::Missing) {
+            ExitStatus::Missing
+        } else if has(FileStatus::Unreadable) {
+            ExitStatus::IoError
+        } else {
+            ExitStatus::Ok
+        }
+    }
 }
 
 /// The entry point for the verification program.
 fn main() {
-    match run() {
-        Ok(_) => println!("Verification successful."),
-        Err(_) => {
+    process::exit(run() as i32);
+}
+
+/// Performs the main steps of the program: parsing arguments and running the selected command.
+fn run() -> ExitStatus {
+    // Parse command line arguments to find out what to do.
+    match parse_arguments() {
+        Command::Verify(args) => run_verify(&args),
+        Command::Generate(args) => run_generate(&args),
+        Command::Sign(args) => run_sign(&args),
+        Command::Diff(args) => run_diff(&args),
+        Command::Update(args) => run_update(&args),
+        Command::Canonicalize(args) => run_canonicalize(&args),
+        Command::Convert(args) => run_convert(&args),
+    }
+}
+
+/// Reads the manifest and verifies the directory against it, printing the outcome.
+///
+/// In text format, human-readable progress and a final verdict are printed. In the machine-readable
+/// formats only the rendered report goes to stdout. Errors are always written to stderr.
+///
+/// Args:
+/// - `args`: The parsed arguments of the verify command.
+///
+/// Returns:
+/// - `ExitStatus`: The exit status describing the outcome.
+fn run_verify(args: &VerifyArgs) -> ExitStatus {
+    let start = Instant::now();
+
+    let status = match verify(args) {
+        Ok(report) => {
+            if args.verbose {
+                print_throughput(report.hashed_bytes(), start.elapsed());
+            }
+            print!("{}", render_report(&report, args.format));
+            ExitStatus::from_report(&report)
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            ExitStatus::from_error(&error)
+        }
+    };
+
+    if args.format == OutputFormat::Text {
+        if status == ExitStatus::Ok {
+            println!("Success: All files in the directory match the manifest entries!");
+            println!("Verification successful.");
+        } else {
             println!("Verification failed.");
-            process::exit(1);
         }
     }
+
+    status
 }
 
-/// Performs the main steps of the program: parsing arguments, reading the manifest, and verifying the directory.
-fn run() -> Result<(), bool> {
-    // Parse command line arguments to get paths for the manifest and the directory.
-    let (manifest_path, directory_path) = parse_arguments();
+/// Prints how much was hashed and how fast to stderr, so machine-readable reports on stdout stay
+/// intact.
+///
+/// Args:
+/// - `hashed_bytes`: The number of bytes hashed.
+/// - `elapsed`: The time verification took, including reading the manifest and walking the
+///   directory.
+fn print_throughput(hashed_bytes: u64, elapsed: Duration) {
+    let mebibytes = hashed_bytes as f64 / (1024.0 * 1024.0);
+    let seconds   = elapsed.as_secs_f64();
+
+    eprintln!("Hashed {:.1} MiB in {:.2}s ({:.1} MiB/s)", mebibytes, seconds, mebibytes / seconds.max(f64::EPSILON));
+}
 
-    // Read and parse the manifest file.
-    let manifest = read_manifest(&manifest_path).map_err(|_| true)?;
+/// Printed when an embedded manifest is used without checking its signature.
+const UNSIGNED_EMBEDDED_WARNING: &str =
+    "Warning: the embedded manifest is not signed by a trusted key, so verification only detects corruption, not tampering.";
+
+/// Checks the manifest's signatures, reads it and verifies the directory against it. If the
+/// directory path is a file, it is verified as a tar, tar.gz or zip archive without extracting it.
+///
+/// Without a manifest path, the manifest embedded at the root of the directory or archive is used,
+/// and it and its signature are left out of the check. A bundle can ship whatever manifest matches
+/// its files, so an embedded manifest must be signed: by the public key, or with an embedded
+/// signature from a trusted key. Only `--allow-unsigned` skips this, with a warning. A manifest
+/// passed by path is read as given, even from inside the bundle, as the caller chose to trust it.
+///
+/// If a public key is given, the manifest's detached signature is checked against the same bytes
+/// that are then parsed. If trusted keys are given, the manifest's embedded signature is checked
+/// while it is read.
+///
+/// Args:
+/// - `args`: The parsed arguments of the verify command.
+///
+/// Returns:
+/// - `Result<VerificationReport>`: The verification report, or the error that stopped verification.
+fn verify(args: &VerifyArgs) -> Result<VerificationReport> {
+    let trusted_keys = args
+        .trusted_key_paths
+        .iter()
+        .map(|path| read_verifying_key(path))
+        .collect::<Result<Vec<_>>>()?;
+    let mut policy = TrustPolicy { trusted_keys, require_signature: args.require_signature };
 
-    // Verify the directory against the manifest.
-    verify_directory(&directory_path, &manifest).map_err(|_| true)?;
+    let mut options = args.options.clone();
+    let manifest = match &args.manifest_path {
+        Some(manifest_path) => {
+            match &args.public_key_path {
+                Some(public_key_path) => load_signed_manifest(manifest_path, public_key_path, args.manifest_format, &policy)?,
+                None => load_manifest(manifest_path, args.manifest_format, &policy)?,
+            }
+        }
+        None => {
+            let embedded = find_embedded_manifest(&args.directory_path)?;
+            match &args.public_key_path {
+                Some(public_key_path) => embedded.verify_signature(&read_verifying_key(public_key_path)?)?,
+                None if !policy.trusted_keys.is_empty() => policy.require_signature = true,
+                None if args.allow_unsigned => eprintln!("{}", UNSIGNED_EMBEDDED_WARNING),
+                None => {
+                    return Err(Error::Signature {
+                        path: embedded.path,
+                        reason: "an embedded manifest can't vouch for its own bundle, pass --pubkey or --trusted-key to check its signature, or --allow-unsigned to only detect corruption".to_string(),
+                    });
+                }
+            }
 
-    // If verification is successful, print a success message.
-    println!("Success: All files in the directory match the manifest entries!");
+            let mut manifest = embedded.read(args.manifest_format, &policy)?;
+            print_warnings(&manifest);
+            embedded.exclude_from(&mut manifest, &mut options);
+            manifest
+        }
+    };
 
-    Ok(())
+    if args.directory_path.is_file() {
+        verify_archive(&args.directory_path, &manifest, &options)
+    } else {
+        verify_directory(&args.directory_path, &manifest, &options)
+    }
 }
 
-/// Parses command-line arguments, extracting paths for the manifest and the target directory.
+/// Reads a manifest, printing any warnings about it to stderr.
+///
+/// Args:
+/// - `manifest_path`: The path to the manifest file.
+/// - `format`: The format of the manifest, `None` to detect it.
+/// - `policy`: The trusted keys and whether a signature is required.
 ///
 /// Returns:
-/// - `(PathBuf, PathBuf)`: Tuple containing the manifest path and the directory path.
-fn parse_arguments() -> (PathBuf, PathBuf)
-{
-    let matches = App::new("Manifest Checker")
-        .version("0.1.0")
-        .author("Usman Mehmood (usmanmehmood55@gmail.com)")
-        .about("Verifies files in a directory against a checksum manifest")
-        .arg(Arg::with_name("manifest")
-            .short('m')
-            .long("manifest")
-            .value_name("FILE")
-            .help("Sets the path to the manifest file")
-            .takes_value(true)
-            .required(true))
-        .arg(Arg::with_name("directory")
-            .short('d')
-            .long("directory")
-            .value_name("DIR")
-            .help("Sets the input directory path")
-            .takes_value(true)
-            .required(true))
-        .get_matches();
+/// - `Result<Manifest>`: The manifest, or the error that stopped it from being read.
+fn load_manifest(manifest_path: &Path, format: Option<ManifestFormat>, policy: &TrustPolicy) -> Result<Manifest> {
+    let manifest = read_manifest_as(manifest_path, format, policy)?;
+    print_warnings(&manifest);
 
-    // Extract and return the manifest and directory paths from the arguments.
-    let manifest_path: PathBuf  = matches.value_of("manifest").unwrap().into();
-    let directory_path: PathBuf = matches.value_of("directory").unwrap().into();
-
-    (manifest_path, directory_path)
+    Ok(manifest)
 }
 
-/// Reads the specified manifest file and parses it into a `Manifest` struct.
+/// Reads a manifest once, checks its detached `.sig` signature against those bytes, then parses
+/// them, printing any warnings about it to stderr. The file is never read twice, so it can't be
+/// swapped between the signature check and parsing.
 ///
 /// Args:
 /// - `manifest_path`: The path to the manifest file.
+/// - `public_key_path`: The path to the Ed25519 public key the manifest must be signed with.
+/// - `format`: The format of the manifest, `None` to detect it.
+/// - `policy`: The trusted keys and whether a signature is required.
 ///
 /// Returns:
-/// - `Result<Manifest, std::io::Error>`: The parsed manifest or an error if reading or parsing failed.
-fn read_manifest(manifest_path: &PathBuf) -> Result<Manifest, std::io::Error>
-{
-    let manifest_file: File     = File::open(manifest_path)?;
-    let reader: BufReader<File> = BufReader::new(manifest_file);
-    let manifest: Manifest      = from_reader(reader).expect("Error parsing JSON");
+/// - `Result<Manifest>`: The manifest, or an error if it could not be read, its signature is
+///   missing or invalid, or it could not be parsed.
+fn load_signed_manifest(manifest_path: &Path, public_key_path: &Path, format: Option<ManifestFormat>, policy: &TrustPolicy) -> Result<Manifest> {
+    let verifying_key  = read_verifying_key(public_key_path)?;
+    let manifest_bytes = fs::read(manifest_path).map_err(|source| Error::Manifest {
+        path: manifest_path.to_path_buf(),
+        reason: format!("cannot open manifest: {}", source),
+    })?;
+
+    let signature_file = signature_path(manifest_path);
+    let signature_text = fs::read_to_string(&signature_file).map_err(|source| Error::Signature {
+        path: signature_file.clone(),
+        reason: format!("cannot read signature: {}", source),
+    })?;
+    check_detached_signature(&manifest_bytes, &signature_file, &signature_text, &verifying_key)?;
+
+    let contents = std::str::from_utf8(&manifest_bytes).map_err(|error| Error::Manifest {
+        path: manifest_path.to_path_buf(),
+        reason: format!("cannot open manifest: {}", error),
+    })?;
+    let manifest = parse_manifest(manifest_path, contents, format, policy)?;
+    print_warnings(&manifest);
 
     Ok(manifest)
 }
 
-/// Verifies each file listed in the manifest exists in the directory and matches the recorded hash.
+/// Prints the warnings raised while reading a manifest to stderr.
+///
+/// Args:
+/// - `manifest`: The manifest that was read.
+fn print_warnings(manifest: &Manifest) {
+    for warning in &manifest.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+/// Signs the manifest with the private key, printing the outcome. The signature is either written
+/// to a detached `.sig` file or embedded in the manifest itself.
 ///
 /// Args:
-/// - `directory_path`: Path to the directory containing the files to verify.
-/// - `manifest`: The manifest containing expected file hashes.
+/// - `args`: The parsed arguments of the sign command.
 ///
 /// Returns:
-/// - `Result<(), bool>`: Ok if all files match, Err otherwise.
-fn verify_directory(directory_path: &PathBuf, manifest: &Manifest) -> Result<(), bool> {
-    // Flag to track if all files match their manifest entry.
-    let mut all_files_match = true;
-
-    // Iterate through each entry in the manifest.
-    for (expected_path_str, expected_hash) in manifest.files.iter() {
-        let file_path = directory_path.join(expected_path_str);
-
-        // Proceed only if the file exists.
-        if file_path.exists() {
-            // Compute the hash of the file.
-            let hash: String = hash_file(&file_path).map_err(|_| true)?;
-
-            // Check if the computed hash matches the expected hash.
-            if &hash == expected_hash {
-                continue;
-            } else {
-                println!("Mismatched hash for file: {}", expected_path_str);
-                println!("Expected: {}", expected_hash);
-                println!("Found:    {}", hash);
-                all_files_match = false;
-            }
+/// - `ExitStatus`: The exit status describing the outcome.
+fn run_sign(args: &SignArgs) -> ExitStatus {
+    let result = if args.embed {
+        embed_manifest_signature(&args.manifest_path, &args.private_key_path).map(|_| args.manifest_path.clone())
+    } else {
+        sign_manifest(&args.manifest_path, &args.private_key_path)
+    };
+
+    match result {
+        Ok(signature_path) => {
+            println!("Signature written to: {}", signature_path.display());
+            ExitStatus::Ok
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            println!("Signing failed.");
+            ExitStatus::from_error(&error)
+        }
+    }
+}
+
+/// Reads two manifests and prints the entries that were added, removed or changed between them.
+///
+/// In text format, a summary line follows the changes. In JSON format only the rendered diff goes
+/// to stdout. Errors are always written to stderr.
+///
+/// Args:
+/// - `args`: The parsed arguments of the diff command.
+///
+/// Returns:
+/// - `ExitStatus`: `Ok` if the manifests have the same entries, `Mismatch` if they differ.
+fn run_diff(args: &DiffArgs) -> ExitStatus {
+    let policy = TrustPolicy::default();
+    let result = load_manifest(&args.old_manifest_path, args.manifest_format, &policy)
+        .and_then(|old| Ok(diff_manifests(&old, &load_manifest(&args.new_manifest_path, args.manifest_format, &policy)?)));
+
+    let diff = match result {
+        Ok(diff) => diff,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return ExitStatus::from_error(&error);
+        }
+    };
+
+    print!("{}", render_diff(&diff, args.format));
+
+    if args.format == OutputFormat::Text {
+        if diff.is_empty() {
+            println!("Manifests have the same entries.");
         } else {
-            println!("Missing file in directory: {}", expected_path_str);
-            all_files_match = false;
+            println!(
+                "{} added, {} removed, {} changed.",
+                diff.with_change(ChangeKind::Added).count(),
+                diff.with_change(ChangeKind::Removed).count(),
+                diff.with_change(ChangeKind::Changed).count()
+            );
         }
     }
 
-    // Return success only if all files matched.
-    if all_files_match {
-        Ok(())
+    if diff.is_empty() {
+        ExitStatus::Ok
     } else {
-        Err(true)
+        ExitStatus::Mismatch
     }
 }
 
-/// Calculates the SHA256 hash of a file at a given path.
+/// Refreshes the manifest from the directory and writes it back if anything changed, printing a
+/// summary of the changes.
+///
+/// Only files whose recorded size or mtime differs, or whose entry records neither, are rehashed.
 ///
 /// Args:
-/// - `path`: A path reference to the file to hash.
+/// - `args`: The parsed arguments of the update command.
 ///
 /// Returns:
-/// - `std::io::Result<String>`: The hexadecimal representation of the file hash, or an error.
-fn hash_file<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
-    let mut file = File::open(path)?;
-    let mut hasher = Sha256::new();
-    let mut buffer = [0; 1024];
+/// - `ExitStatus`: The exit status describing the outcome.
+fn run_update(args: &UpdateArgs) -> ExitStatus {
+    let result = load_manifest(&args.manifest_path, args.manifest_format, &TrustPolicy::default()).and_then(|mut manifest| {
+        let summary = update_manifest(&args.directory_path, &mut manifest, &args.options)?;
+        if summary.is_changed() {
+            write_manifest(&args.manifest_path, &manifest)?;
+        }
+
+        Ok(summary)
+    });
 
-    // Read the file content in chunks and update the hash.
-    loop {
-        let count = file.read(&mut buffer)?;
-        if count == 0 {
-            break; // End of file reached.
+    let summary = match result {
+        Ok(summary) => summary,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            println!("Manifest update failed.");
+            return ExitStatus::from_error(&error);
         }
-        hasher.update(&buffer[..count]);
+    };
+
+    for path in &summary.added {
+        println!("Added:   {}", path);
+    }
+    for path in &summary.updated {
+        println!("Updated: {}", path);
+    }
+    for path in &summary.removed {
+        println!("Removed: {}", path);
+    }
+    for path in &summary.missing {
+        println!("Missing: {} (kept, use --prune to remove)", path);
+    }
+
+    println!(
+        "{} added, {} updated, {} removed, {} unchanged.",
+        summary.added.len(),
+        summary.updated.len(),
+        summary.removed.len(),
+        summary.unchanged
+    );
+
+    if !summary.is_changed() {
+        println!("Manifest is up to date.");
+        return ExitStatus::Ok;
+    }
+
+    if summary.signature_removed {
+        println!("The embedded signature no longer matches and was removed; sign the manifest again.");
+    }
+    if signature_path(&args.manifest_path).exists() {
+        println!("The detached signature no longer matches; sign the manifest again.");
     }
+    println!("Manifest written to: {}", args.manifest_path.display());
 
-    // Return the final hash in hexadecimal format.
-    Ok(format!("{:x}", hasher.finalize()))
+    ExitStatus::Ok
+}
+
+/// Rewrites the manifest into its canonical form and writes it to the output path, printing the
+/// outcome. An embedded signature that no longer matches the canonical form is removed.
+///
+/// Args:
+/// - `args`: The parsed arguments of the canonicalize command.
+///
+/// Returns:
+/// - `ExitStatus`: The exit status describing the outcome.
+fn run_canonicalize(args: &CanonicalizeArgs) -> ExitStatus {
+    let result = fs::read(&args.manifest_path)
+        .map_err(|error| Error::io(&args.manifest_path, error))
+        .and_then(|original| {
+            let mut manifest      = load_manifest(&args.manifest_path, args.manifest_format, &TrustPolicy::default())?;
+            let signature_removed = manifest.canonicalize(&args.manifest_path)?;
+            let text              = serialize_manifest(&args.output_path, &manifest)?;
+            let changed           = text.as_bytes() != original;
+
+            // Rewriting an already canonical manifest in place would only touch its mtime.
+            if changed || args.output_path != args.manifest_path {
+                fs::write(&args.output_path, text).map_err(|error| Error::io(&args.output_path, error))?;
+            }
+            Ok((changed, signature_removed))
+        });
+
+    match result {
+        Ok((changed, signature_removed)) => {
+            if !changed {
+                println!("Manifest was already canonical.");
+            }
+            if signature_removed {
+                println!("The embedded signature no longer matches and was removed; sign the manifest again.");
+            }
+            if changed && args.output_path == args.manifest_path && signature_path(&args.manifest_path).exists() {
+                println!("The detached signature no longer matches; sign the manifest again.");
+            }
+            if changed || args.output_path != args.manifest_path {
+                println!("Manifest written to: {}", args.output_path.display());
+            }
+            ExitStatus::Ok
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            println!("Canonicalization failed.");
+            ExitStatus::from_error(&error)
+        }
+    }
+}
+
+/// Reads a manifest in any format and writes it in the requested one, printing the outcome.
+///
+/// Args:
+/// - `args`: The parsed arguments of the convert command.
+///
+/// Returns:
+/// - `ExitStatus`: The exit status describing the outcome.
+fn run_convert(args: &ConvertArgs) -> ExitStatus {
+    let result = load_manifest(&args.manifest_path, args.manifest_format, &TrustPolicy::default()).and_then(|mut manifest| {
+        manifest.format = args.format;
+
+        // A checksum list has no room for an embedded signature.
+        let signature_removed = args.format == ManifestFormat::Sha256sum && manifest.signature.take().is_some();
+
+        write_manifest(&args.output_path, &manifest)?;
+        Ok((manifest, signature_removed))
+    });
+
+    match result {
+        Ok((manifest, signature_removed)) => {
+            if drops_details(&manifest) {
+                println!("{}", DROPPED_DETAILS_NOTE);
+            }
+            if signature_removed {
+                println!("The embedded signature can't be kept in the sha256sum format and was removed; use a detached signature instead.");
+            }
+            println!("Manifest written to: {}", args.output_path.display());
+            ExitStatus::Ok
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            println!("Conversion failed.");
+            ExitStatus::from_error(&error)
+        }
+    }
+}
+
+/// Printed when a manifest is written in a format that leaves some of its details out.
+const DROPPED_DETAILS_NOTE: &str =
+    "The sha256sum format only records digests, so sizes, metadata, directory entries, ignore patterns and other fields were left out.";
+
+/// Returns `true` if writing the manifest in its format leaves out details it records.
+///
+/// Args:
+/// - `manifest`: The manifest being written.
+fn drops_details(manifest: &Manifest) -> bool {
+    let has_details = |entry: &FileEntry| {
+        entry.hash.is_none() || entry.size.is_some() || entry.mtime.is_some() || entry.mode.is_some() || entry.uid.is_some() || entry.gid.is_some() || entry.kind.is_some()
+    };
+
+    manifest.format == ManifestFormat::Sha256sum
+        && (!manifest.ignore.is_empty() || !manifest.extra.is_empty() || manifest.files.values().any(has_details))
+}
+
+/// Generates a manifest for the directory and writes it to the output path, printing the outcome.
+///
+/// Args:
+/// - `args`: The parsed arguments of the generate command.
+///
+/// Returns:
+/// - `ExitStatus`: The exit status describing the outcome.
+fn run_generate(args: &GenerateArgs) -> ExitStatus {
+    let result = generate_manifest(&args.directory_path, &args.options).and_then(|mut manifest| {
+        manifest.format = args.format;
+        write_manifest(&args.output_path, &manifest)?;
+        Ok(manifest)
+    });
+
+    match result {
+        Ok(manifest) => {
+            if drops_details(&manifest) {
+                println!("{}", DROPPED_DETAILS_NOTE);
+            }
+            println!("Manifest written to: {}", args.output_path.display());
+            ExitStatus::Ok
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            println!("Manifest generation failed.");
+            ExitStatus::from_error(&error)
+        }
+    }
+}
+
+/// Collects the `--include` and `--exclude` globs of a command.
+///
+/// Args:
+/// - `matches`: The parsed arguments of the command.
+///
+/// Returns:
+/// - `PathRules`: The include and exclude globs, in the order they were given.
+fn path_rules(matches: &ArgMatches) -> PathRules {
+    let globs = |name: &str| matches.values_of(name).map_or_else(Vec::new, |globs| globs.map(String::from).collect());
+
+    PathRules { include: globs("include"), exclude: globs("exclude") }
+}
+
+/// Returns the manifest format given with `--manifest-format`, if any.
+///
+/// Args:
+/// - `matches`: The parsed arguments of the command.
+fn manifest_format(matches: &ArgMatches) -> Option<ManifestFormat> {
+    matches.value_of("manifest-format").map(|format| format.parse().unwrap())
+}
+
+/// Returns the format a manifest is written in: the one given on the command line, or else the one
+/// named by the output path's extension, or else JSON.
+///
+/// Args:
+/// - `format`: The format given on the command line, if any.
+/// - `output_path`: The path the manifest is written to.
+fn output_format(format: Option<&str>, output_path: &Path) -> ManifestFormat {
+    format.map_or_else(|| ManifestFormat::from_extension(output_path).unwrap_or_default(), |format| format.parse().unwrap())
+}
+
+/// Returns the `--manifest-format` argument shared by the commands that read a manifest.
+fn manifest_format_arg() -> Arg<'static> {
+    Arg::with_name("manifest-format")
+        .long("manifest-format")
+        .value_name("FORMAT")
+        .help("Sets the format of the manifest instead of detecting it from its extension or contents")
+        .takes_value(true)
+        .possible_values(ManifestFormat::ALL.map(|format| format.as_str()))
+}
+
+/// Returns the `--symlinks` argument shared by the commands that walk a directory.
+///
+/// Args:
+/// - `default`: The policy used if none is given: verification follows symlinks, while generation
+///   and update record them as symlink entries.
+fn symlinks_arg(default: SymlinkPolicy) -> Arg<'static> {
+    Arg::with_name("symlinks")
+        .long("symlinks")
+        .value_name("POLICY")
+        .help("Sets how symlinks are treated: followed if they stay inside the directory, recorded by their target, or forbidden")
+        .takes_value(true)
+        .possible_values(SymlinkPolicy::ALL.map(|policy| policy.as_str()))
+        .default_value(default.as_str())
+}
+
+/// Returns the repeatable `--include` and `--exclude` glob arguments, read back by `path_rules`.
+///
+/// Args:
+/// - `include_help`: The help text of `--include`, saying what the command does with matching files.
+/// - `exclude_help`: The help text of `--exclude`.
+fn path_rule_args(include_help: &'static str, exclude_help: &'static str) -> [Arg<'static>; 2] {
+    let glob_arg = |name: &'static str, help: &'static str| {
+        Arg::with_name(name)
+            .long(name)
+            .value_name("GLOB")
+            .help(help)
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .validator(validate_glob)
+    };
+
+    [glob_arg("include", include_help), glob_arg("exclude", exclude_help)]
+}
+
+/// Returns the arguments of the verify command, which are accepted both with and without the
+/// `verify` subcommand.
+fn verify_args() -> Vec<Arg<'static>> {
+    let mut args = vec![
+        Arg::with_name("manifest")
+            .short('m')
+            .long("manifest")
+            .value_name("FILE")
+            .help("Sets the path to the manifest file [default: the manifest embedded at the root of the directory or archive]")
+            .takes_value(true),
+        Arg::with_name("directory")
+            .short('d')
+            .long("directory")
+            .value_name("DIR")
+            .help("Sets the input directory path, or a tar, tar.gz or zip archive to verify without extracting")
+            .takes_value(true)
+            .required(true),
+        Arg::with_name("strict")
+            .short('s')
+            .long("strict")
+            .help("Fails verification if the directory contains files not listed in the manifest"),
+        Arg::with_name("pubkey")
+            .short('k')
+            .long("pubkey")
+            .value_name("FILE")
+            .help("Requires the manifest's detached .sig signature to be valid for this Ed25519 public key")
+            .takes_value(true),
+        Arg::with_name("trusted-key")
+            .short('t')
+            .long("trusted-key")
+            .value_name("FILE")
+            .help("Trusts this Ed25519 public key for the manifest's embedded signature, may be repeated")
+            .takes_value(true)
+            .multiple_occurrences(true),
+        Arg::with_name("require-signature")
+            .long("require-signature")
+            .help("Refuses manifests without an embedded signature from a trusted key")
+            .requires("trusted-key"),
+        Arg::with_name("allow-unsigned")
+            .long("allow-unsigned")
+            .help("Verifies against a manifest found in the bundle without checking its signature, which only detects corruption. A manifest passed with --manifest is never refused for being unsigned")
+            .conflicts_with_all(&["manifest", "pubkey", "trusted-key"]),
+        symlinks_arg(SymlinkPolicy::Follow),
+        Arg::with_name("case-insensitive")
+            .long("case-insensitive")
+            .help("Matches manifest paths to files whose names differ only in case, for manifests built on case-insensitive file systems"),
+    ];
+    args.extend(path_rule_args(
+        "Only reports unlisted files matching this glob in strict mode, may be repeated",
+        "Never reports unlisted files matching this glob in strict mode, may be repeated",
+    ));
+    args.extend([
+        Arg::with_name("ignore-metadata")
+            .long("ignore-metadata")
+            .help("Ignores the type, permission bits and ownership recorded in the manifest"),
+        manifest_format_arg(),
+        Arg::with_name("format")
+            .short('f')
+            .long("format")
+            .value_name("FORMAT")
+            .help("Sets the format of the verification report")
+            .takes_value(true)
+            .possible_values(["text", "json", "junit"])
+            .default_value("text"),
+        Arg::with_name("verbose")
+            .short('v')
+            .long("verbose")
+            .help("Prints how many bytes were hashed and the throughput to stderr"),
+        Arg::with_name("jobs")
+            .short('j')
+            .long("jobs")
+            .value_name("N")
+            .help("Sets the number of files hashed concurrently [default: number of CPUs]")
+            .takes_value(true)
+            .validator(|value| match value.parse::<usize>() {
+                Ok(jobs) if jobs > 0 => Ok(()),
+                _ => Err("must be a positive integer"),
+            }),
+    ]);
+
+    args
+}
+
+/// Parses command-line arguments, extracting the command to run and its paths.
+///
+/// Returns:
+/// - `Command`: The selected command along with its arguments.
+fn parse_arguments() -> Command
+{
+    let matches = App::new("Manifest Checker")
+        .version("0.1.0")
+        .author("Usman Mehmood (usmanmehmood55@gmail.com)")
+        .about("Verifies files in a directory against a checksum manifest")
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .args(verify_args())
+        .subcommand(App::new("verify")
+            .about("Verifies files in a directory or archive against a checksum manifest, the same as without a subcommand")
+            .args(verify_args()))
+        .subcommand(App::new("generate")
+            .about("Generates a manifest from the files in a directory")
+            .arg(Arg::with_name("directory")
+                .short('d')
+                .long("directory")
+                .value_name("DIR")
+                .help("Sets the input directory path")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Sets the path the manifest file is written to")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("algorithm")
+                .short('a')
+                .long("algorithm")
+                .value_name("ALGORITHM")
+                .help("Sets the hash algorithm the files are recorded with")
+                .takes_value(true)
+                .possible_values(HashAlgorithm::ALL.map(|algorithm| algorithm.as_str()))
+                .default_value("sha256"))
+            .arg(symlinks_arg(SymlinkPolicy::Record))
+            .arg(Arg::with_name("sizes")
+                .long("sizes")
+                .help("Records the size of each file next to its hash, so truncated files fail before hashing"))
+            .arg(Arg::with_name("mtimes")
+                .long("mtimes")
+                .help("Records the size and modification time of each file, so update only rehashes files that changed"))
+            .arg(Arg::with_name("metadata")
+                .long("metadata")
+                .help("Records the type, permission bits and ownership of each file"))
+            .args(path_rule_args(
+                "Only records files matching this glob, may be repeated",
+                "Never records files matching this glob, may be repeated",
+            ))
+            .arg(Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Sets the format the manifest is written in [default: from the output extension, or json]")
+                .takes_value(true)
+                .possible_values(ManifestFormat::ALL.map(|format| format.as_str()))))
+        .subcommand(App::new("sign")
+            .about("Signs a manifest with an Ed25519 private key, writing a detached .sig file next to it")
+            .arg(Arg::with_name("manifest")
+                .short('m')
+                .long("manifest")
+                .value_name("FILE")
+                .help("Sets the path to the manifest file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("key")
+                .short('k')
+                .long("key")
+                .value_name("FILE")
+                .help("Sets the path to the Ed25519 private key file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("embed")
+                .short('e')
+                .long("embed")
+                .help("Embeds the signature in the manifest instead of writing a detached .sig file")))
+        .subcommand(App::new("update")
+            .about("Refreshes a manifest in place, rehashing only files whose recorded size or mtime changed")
+            .arg(Arg::with_name("manifest")
+                .short('m')
+                .long("manifest")
+                .value_name("FILE")
+                .help("Sets the path to the manifest file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("directory")
+                .short('d')
+                .long("directory")
+                .value_name("DIR")
+                .help("Sets the input directory path")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("prune")
+                .long("prune")
+                .help("Removes entries whose path no longer exists instead of keeping them"))
+            .arg(manifest_format_arg())
+            .arg(symlinks_arg(SymlinkPolicy::Record))
+            .args(path_rule_args(
+                "Only adds new files matching this glob, may be repeated",
+                "Never adds new files matching this glob, may be repeated",
+            )))
+        .subcommand(App::new("canonicalize")
+            .about("Rewrites a manifest with sorted, normalized paths and lowercase hashes")
+            .arg(Arg::with_name("manifest")
+                .short('m')
+                .long("manifest")
+                .value_name("FILE")
+                .help("Sets the path to the manifest file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Sets the path the canonical manifest is written to [default: the manifest itself]")
+                .takes_value(true))
+            .arg(manifest_format_arg()))
+        .subcommand(App::new("convert")
+            .about("Translates a manifest between JSON, YAML, TOML and sha256sum checksum lists")
+            .arg(Arg::with_name("manifest")
+                .short('m')
+                .long("manifest")
+                .value_name("FILE")
+                .help("Sets the path to the manifest file, in any supported format")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Sets the path the converted manifest is written to")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("to")
+                .long("to")
+                .value_name("FORMAT")
+                .help("Sets the format the manifest is converted to [default: from the output extension, or json]")
+                .takes_value(true)
+                .possible_values(ManifestFormat::ALL.map(|format| format.as_str())))
+            .arg(manifest_format_arg()))
+        .subcommand(App::new("diff")
+            .about("Lists the entries added, removed or changed between two manifests")
+            .arg(Arg::with_name("old")
+                .value_name("OLD")
+                .help("Sets the path to the earlier manifest")
+                .required(true))
+            .arg(Arg::with_name("new")
+                .value_name("NEW")
+                .help("Sets the path to the later manifest")
+                .required(true))
+            .arg(manifest_format_arg()
+                .help("Sets the format of both manifests instead of detecting it from its extension or contents"))
+            .arg(Arg::with_name("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .help("Sets the format of the diff")
+                .takes_value(true)
+                .possible_values(["text", "json"])
+                .default_value("text")))
+        .get_matches();
+
+    if let Some(generate_matches) = matches.subcommand_matches("generate") {
+        let directory_path: PathBuf = generate_matches.value_of("directory").unwrap().into();
+        let output_path: PathBuf    = generate_matches.value_of("output").unwrap().into();
+        let algorithm: HashAlgorithm = generate_matches.value_of("algorithm").unwrap().parse().unwrap();
+        let symlinks: SymlinkPolicy  = generate_matches.value_of("symlinks").unwrap().parse().unwrap();
+
+        let record_sizes: bool       = generate_matches.is_present("sizes");
+        let record_mtimes: bool      = generate_matches.is_present("mtimes");
+        let record_metadata: bool    = generate_matches.is_present("metadata");
+
+        let mut paths: PathRules     = path_rules(generate_matches);
+        let format: ManifestFormat   = output_format(generate_matches.value_of("format"), &output_path);
+
+        // A manifest written into the directory must not record its own previous version.
+        paths.exclude_manifest(&directory_path, &output_path);
+
+        let options = GenerateOptions { algorithm, symlinks, record_sizes, record_mtimes, record_metadata, paths };
+
+        return Command::Generate(GenerateArgs { directory_path, output_path, options, format });
+    }
+
+    if let Some(sign_matches) = matches.subcommand_matches("sign") {
+        let manifest_path: PathBuf    = sign_matches.value_of("manifest").unwrap().into();
+        let private_key_path: PathBuf = sign_matches.value_of("key").unwrap().into();
+        let embed: bool               = sign_matches.is_present("embed");
+
+        return Command::Sign(SignArgs { manifest_path, private_key_path, embed });
+    }
+
+    if let Some(update_matches) = matches.subcommand_matches("update") {
+        let manifest_path: PathBuf  = update_matches.value_of("manifest").unwrap().into();
+        let directory_path: PathBuf = update_matches.value_of("directory").unwrap().into();
+        let symlinks: SymlinkPolicy = update_matches.value_of("symlinks").unwrap().parse().unwrap();
+        let prune: bool             = update_matches.is_present("prune");
+        let mut paths: PathRules    = path_rules(update_matches);
+        let manifest_format         = manifest_format(update_matches);
+
+        // Writing the manifest changes its hash, so it must never list itself or its signature.
+        paths.exclude_manifest(&directory_path, &manifest_path);
+
+        let options = UpdateOptions { symlinks, prune, paths };
+
+        return Command::Update(UpdateArgs { manifest_path, manifest_format, directory_path, options });
+    }
+
+    if let Some(canonicalize_matches) = matches.subcommand_matches("canonicalize") {
+        let manifest_path: PathBuf = canonicalize_matches.value_of("manifest").unwrap().into();
+        let output_path: PathBuf   = canonicalize_matches.value_of("output").map_or_else(|| manifest_path.clone(), PathBuf::from);
+        let manifest_format        = manifest_format(canonicalize_matches);
+
+        return Command::Canonicalize(CanonicalizeArgs { manifest_path, manifest_format, output_path });
+    }
+
+    if let Some(convert_matches) = matches.subcommand_matches("convert") {
+        let manifest_path: PathBuf = convert_matches.value_of("manifest").unwrap().into();
+        let output_path: PathBuf   = convert_matches.value_of("output").unwrap().into();
+        let format: ManifestFormat = output_format(convert_matches.value_of("to"), &output_path);
+        let manifest_format        = manifest_format(convert_matches);
+
+        return Command::Convert(ConvertArgs { manifest_path, manifest_format, output_path, format });
+    }
+
+    if let Some(diff_matches) = matches.subcommand_matches("diff") {
+        let old_manifest_path: PathBuf = diff_matches.value_of("old").unwrap().into();
+        let new_manifest_path: PathBuf = diff_matches.value_of("new").unwrap().into();
+        let format: OutputFormat       = diff_matches.value_of("format").unwrap().parse().unwrap();
+        let manifest_format            = manifest_format(diff_matches);
+
+        return Command::Diff(DiffArgs { old_manifest_path, new_manifest_path, manifest_format, format });
+    }
+
+    // The verify arguments are accepted both with and without the verify subcommand.
+    let matches = matches.subcommand_matches("verify").unwrap_or(&matches);
+
+    // Extract and return the manifest and directory paths from the arguments.
+    let manifest_path           = matches.value_of("manifest").map(PathBuf::from);
+    let manifest_format         = manifest_format(matches);
+    let directory_path: PathBuf = matches.value_of("directory").unwrap().into();
+    let public_key_path         = matches.value_of("pubkey").map(PathBuf::from);
+    let trusted_key_paths       = matches.values_of("trusted-key").map_or_else(Vec::new, |paths| paths.map(PathBuf::from).collect());
+    let require_signature: bool = matches.is_present("require-signature");
+    let allow_unsigned: bool    = matches.is_present("allow-unsigned");
+    let strict: bool            = matches.is_present("strict");
+    let format: OutputFormat    = matches.value_of("format").unwrap().parse().unwrap();
+    let verbose: bool           = matches.is_present("verbose");
+    let jobs: usize             = matches.value_of("jobs").map_or_else(default_jobs, |jobs| jobs.parse().unwrap());
+
+    let symlinks: SymlinkPolicy = matches.value_of("symlinks").unwrap().parse().unwrap();
+
+    let ignore_metadata: bool   = matches.is_present("ignore-metadata");
+    let case_insensitive: bool  = matches.is_present("case-insensitive");
+    let paths: PathRules        = path_rules(matches);
+
+    let options = VerifyOptions { strict, jobs, symlinks, ignore_metadata, case_insensitive, paths };
+
+    Command::Verify(VerifyArgs {
+        manifest_path,
+        manifest_format,
+        directory_path,
+        public_key_path,
+        trusted_key_paths,
+        require_signature,
+        allow_unsigned,
+        options,
+        format,
+        verbose,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use ed25519_dalek::SigningKey;
+
+    /// Writes a bundle with one file and an unsigned `MANIFEST.json` listing it.
+    fn bundle() -> tempfile::TempDir {
+        let directory     = tempfile::tempdir().unwrap();
+        let manifest_path = directory.path().join("MANIFEST.json");
+        fs::write(directory.path().join("a.txt"), "a").unwrap();
+
+        let mut options = GenerateOptions::default();
+        options.paths.exclude_manifest(directory.path(), &manifest_path);
+        write_manifest(&manifest_path, &generate_manifest(directory.path(), &options).unwrap()).unwrap();
+
+        directory
+    }
+
+    /// Returns the arguments of `verify -s -d <directory>`.
+    fn verify_args(directory_path: &Path) -> VerifyArgs {
+        VerifyArgs {
+            manifest_path: None,
+            manifest_format: None,
+            directory_path: directory_path.to_path_buf(),
+            public_key_path: None,
+            trusted_key_paths: Vec::new(),
+            require_signature: false,
+            allow_unsigned: false,
+            options: VerifyOptions { strict: true, ..VerifyOptions::default() },
+            format: OutputFormat::Text,
+            verbose: false,
+        }
+    }
+
+    #[test]
+    fn unsigned_embedded_manifests_are_refused_without_a_key() {
+        let directory = bundle();
+
+        let error = verify(&verify_args(directory.path())).unwrap_err();
+        assert!(matches!(error, Error::Signature { .. }), "{}", error);
+        assert_eq!(ExitStatus::from_error(&error), ExitStatus::ManifestError);
+    }
+
+    #[test]
+    fn allow_unsigned_verifies_against_an_unsigned_embedded_manifest() {
+        let directory = bundle();
+
+        let report = verify(&VerifyArgs { allow_unsigned: true, ..verify_args(directory.path()) }).unwrap();
+        assert!(report.is_success());
+        assert_eq!(report.files.len(), 1);
+    }
+
+    #[test]
+    fn embedded_manifests_verify_against_the_key_they_are_signed_with() {
+        let directory   = bundle();
+        let keys        = tempfile::tempdir().unwrap();
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let private_key = keys.path().join("private.key");
+        let public_key  = keys.path().join("public.key");
+        let other_key   = keys.path().join("other.key");
+        fs::write(&private_key, BASE64.encode(signing_key.to_bytes())).unwrap();
+        fs::write(&public_key, BASE64.encode(signing_key.verifying_key().to_bytes())).unwrap();
+        fs::write(&other_key, BASE64.encode(SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes())).unwrap();
+        sign_manifest(&directory.path().join("MANIFEST.json"), &private_key).unwrap();
+
+        let report = verify(&VerifyArgs { public_key_path: Some(public_key), ..verify_args(directory.path()) }).unwrap();
+        assert!(report.is_success(), "the manifest and its signature are left out of the strict check");
+
+        let error = verify(&VerifyArgs { public_key_path: Some(other_key), ..verify_args(directory.path()) }).unwrap_err();
+        assert!(matches!(error, Error::Signature { .. }), "{}", error);
+    }
+
+    #[test]
+    fn manifests_passed_by_path_are_not_refused_for_being_unsigned() {
+        let directory = bundle();
+        let args      = VerifyArgs {
+            manifest_path: Some(directory.path().join("MANIFEST.json")),
+            options: VerifyOptions::default(),
+            ..verify_args(directory.path())
+        };
+
+        assert!(verify(&args).unwrap().is_success());
+    }
 }
@@ -0,0 +1,1258 @@
+use crate::checksum::{parse_checksums, serialize_checksums};
+use crate::error::{Error, Result};
+use crate::filter::{validate_glob, PathFilter, PathRules};
+use crate::hash::{hash_file, HashAlgorithm};
+use crate::metadata::{octal_mode, read_metadata, EntryType};
+use crate::path::{normalize_manifest_path, resolve_entry, ResolvedPath, SymlinkPolicy};
+use crate::signature::{canonical_signing_bytes, check_embedded_signature, ManifestSignature, TrustPolicy};
+use crate::verify::{list_directory_files, list_empty_directories};
+use indexmap::IndexMap;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::to_string_pretty;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+/// Represents the expected structure of the manifest file.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Manifest {
+    /// The algorithm used for entries that don't name their own, SHA256 if absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<HashAlgorithm>,
+    /// Maps forward-slash paths, relative to the verified directory, to their expected hashes. Entries
+    /// keep the order they were read or generated in.
+    pub files: IndexMap<String, FileEntry>,
+    /// Globs of unlisted paths that strict verification expects and doesn't report, such as `*.log`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// A signature covering the rest of the manifest, checked when trusted keys are configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ManifestSignature>,
+    /// Top-level fields this tool doesn't use, such as a release version, kept in their order so
+    /// they are written back unchanged.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, serde_json::Value>,
+    /// The format the manifest was read in, and is written back in.
+    #[serde(skip)]
+    pub format: ManifestFormat,
+    /// Problems found and fixed while reading the manifest, such as backslash separators.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+/// The file formats a manifest can be read and written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// The JSON manifest schema, which can record every detail.
+    #[default]
+    Json,
+    /// The manifest schema written as YAML.
+    Yaml,
+    /// The manifest schema written as TOML.
+    Toml,
+    /// A checksum list in the coreutils `sha256sum` format, which only records digests.
+    Sha256sum,
+}
+
+impl ManifestFormat {
+    /// All supported formats, in the order they are listed in help texts.
+    pub const ALL: [ManifestFormat; 4] = [ManifestFormat::Json, ManifestFormat::Yaml, ManifestFormat::Toml, ManifestFormat::Sha256sum];
+
+    /// Returns the name used for this format on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ManifestFormat::Json      => "json",
+            ManifestFormat::Yaml      => "yaml",
+            ManifestFormat::Toml      => "toml",
+            ManifestFormat::Sha256sum => "sha256sum",
+        }
+    }
+
+    /// Returns the format named by a file's extension: `.json`, `.yaml` or `.yml`, or `.toml`.
+    ///
+    /// Args:
+    /// - `path`: The path of the manifest file.
+    ///
+    /// Returns:
+    /// - `Option<ManifestFormat>`: The format, or `None` if the extension doesn't name one.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "json"         => Some(ManifestFormat::Json),
+            "yaml" | "yml" => Some(ManifestFormat::Yaml),
+            "toml"         => Some(ManifestFormat::Toml),
+            _              => None,
+        }
+    }
+
+    /// Detects the format of a manifest from its extension, or failing that from its contents: JSON
+    /// manifests are objects, anything else is read as a checksum list.
+    ///
+    /// Args:
+    /// - `path`: The path of the manifest file.
+    /// - `contents`: The manifest file's contents.
+    pub fn detect(path: &Path, contents: &str) -> Self {
+        if let Some(format) = ManifestFormat::from_extension(path) {
+            return format;
+        }
+
+        // An empty file is read as JSON, so it is reported as a truncated manifest.
+        if contents.trim().is_empty() || contents.trim_start().starts_with('{') {
+            ManifestFormat::Json
+        } else {
+            ManifestFormat::Sha256sum
+        }
+    }
+}
+
+impl fmt::Display for ManifestFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ManifestFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        ManifestFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str() == value)
+            .ok_or_else(|| format!("unknown manifest format: {}", value))
+    }
+}
+
+impl Manifest {
+    /// Returns the algorithm an entry is hashed with, falling back to the manifest-wide default.
+    ///
+    /// Args:
+    /// - `entry`: An entry of this manifest.
+    pub fn algorithm_for(&self, entry: &FileEntry) -> HashAlgorithm {
+        entry.algorithm.or(self.algorithm).unwrap_or_default()
+    }
+
+    /// Checks every entry for an empty or absolute path, a path that escapes the directory through
+    /// `..`, and for a hash that isn't valid hex of the right length for its algorithm. Entries are
+    /// checked in path order, so the reported entry is always the same. Every `ignore` glob must be
+    /// valid.
+    ///
+    /// Args:
+    /// - `manifest_path`: The path the manifest was read from, used in error messages.
+    ///
+    /// Returns:
+    /// - `Result<()>`: Ok if every entry is valid, or an error naming the first invalid entry.
+    pub fn validate(&self, manifest_path: &Path) -> Result<()> {
+        for pattern in &self.ignore {
+            validate_glob(pattern)
+                .map_err(|reason| Error::pattern(Some(manifest_path), format!("invalid ignore pattern `{}`: {}", pattern, reason)))?;
+        }
+
+        let mut keys: Vec<&String> = self.files.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let entry     = &self.files[key];
+            let algorithm = self.algorithm_for(entry);
+            let invalid   = |reason: String| Error::InvalidEntry { path: manifest_path.to_path_buf(), key: key.clone(), reason };
+
+            if key.is_empty() {
+                return Err(invalid("path is empty".to_string()));
+            }
+            if is_absolute_manifest_path(key) {
+                return Err(invalid("path must be relative to the verified directory".to_string()));
+            }
+            match normalize_manifest_path(key).as_deref() {
+                None => return Err(invalid("path escapes the verified directory".to_string())),
+                Some("") => return Err(invalid("path names the verified directory itself".to_string())),
+                Some(_) => {}
+            }
+            if entry.target.as_deref() == Some("") {
+                return Err(invalid("symlink target is empty".to_string()));
+            }
+
+            let Some(hash) = &entry.hash else {
+                continue;
+            };
+            if !hash.chars().all(|character| character.is_ascii_hexdigit()) {
+                return Err(invalid(format!("hash \"{}\" is not hexadecimal", hash)));
+            }
+            if hash.len() != algorithm.hex_len() {
+                return Err(invalid(format!(
+                    "{} hash must be {} hex characters, found {}",
+                    algorithm,
+                    algorithm.hex_len(),
+                    hash.len()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites the manifest into its canonical form: forward-slash paths without `.` or `..`
+    /// components, lowercase hashes, entries sorted by path and other top-level fields sorted by
+    /// name. A canonical manifest always serializes to the same bytes. Lowercasing a hash or
+    /// normalizing a path changes what an embedded signature covers, so the signature is removed
+    /// if that happens.
+    ///
+    /// Args:
+    /// - `manifest_path`: The path the manifest was read from, used in error messages.
+    ///
+    /// Returns:
+    /// - `Result<bool>`: Whether an embedded signature was removed, or an error naming an entry
+    ///   whose path escapes the directory or names the same path as another entry. The manifest
+    ///   is unchanged on error.
+    pub fn canonicalize(&mut self, manifest_path: &Path) -> Result<bool> {
+        let signed_bytes = canonical_signing_bytes(self);
+        let mut files: IndexMap<String, FileEntry> = IndexMap::with_capacity(self.files.len());
+
+        for (key, entry) in &self.files {
+            let invalid = |reason: String| Error::InvalidEntry { path: manifest_path.to_path_buf(), key: key.clone(), reason };
+
+            let Some(path) = normalize_manifest_path(key).filter(|path| !path.is_empty()) else {
+                return Err(invalid("path escapes the verified directory".to_string()));
+            };
+            if files.contains_key(&path) {
+                return Err(invalid(format!("path is listed twice, as \"{}\"", path)));
+            }
+
+            let mut entry = entry.clone();
+            entry.hash    = entry.hash.map(|hash| hash.to_ascii_lowercase());
+            files.insert(path, entry);
+        }
+
+        files.sort_keys();
+        self.files = files;
+        self.extra.sort_keys();
+
+        Ok(canonical_signing_bytes(self) != signed_bytes && self.signature.take().is_some())
+    }
+}
+
+/// Returns `true` if a manifest path is absolute on any platform: rooted with a slash or
+/// backslash, or starting with a Windows drive letter.
+fn is_absolute_manifest_path(key: &str) -> bool {
+    let bytes = key.as_bytes();
+    let has_drive_letter = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+
+    key.starts_with('/') || key.starts_with('\\') || has_drive_letter
+}
+
+/// The expected hash, and optionally size, of a single file in the manifest, the expected target
+/// of a symlink, or a directory that must exist.
+///
+/// In JSON an entry is either a plain hex digest, a digest prefixed with its algorithm such as
+/// `"sha512:abcd…"`, or an object. Objects hold the digest either under `hash`, optionally with an
+/// `algorithm`, or under the name of its algorithm, and may record the file `size` in bytes:
+/// `{ "hash": "…", "algorithm": "blake3" }` or `{ "sha256": "…", "size": 12345 }`. Objects may
+/// also record the Unix `mode` (an octal string), `uid`, `gid` and `type` of the path.
+///
+/// Symlink entries record the link `target` instead of a digest, as in
+/// `{ "type": "symlink", "target": "libfoo.so.1" }`. Directory entries have neither, as in
+/// `{ "type": "dir", "mode": "0750" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "RawFileEntry", into = "RawFileEntry")]
+pub struct FileEntry {
+    /// The hexadecimal digest, without any algorithm prefix. `None` for symlink and directory entries.
+    pub hash: Option<String>,
+    /// The algorithm of this entry, overriding the manifest-wide default.
+    pub algorithm: Option<HashAlgorithm>,
+    /// The expected file size in bytes, checked before the file is hashed.
+    pub size: Option<u64>,
+    /// The modification time of the file when it was hashed, in nanoseconds since the Unix epoch.
+    /// Not verified; `update` only skips rehashing files whose size and mtime are both unchanged.
+    pub mtime: Option<u64>,
+    /// The expected permission bits, such as `0o755`.
+    pub mode: Option<u32>,
+    /// The expected owner user id.
+    pub uid: Option<u32>,
+    /// The expected owner group id.
+    pub gid: Option<u32>,
+    /// The expected type of the path.
+    pub kind: Option<EntryType>,
+    /// The exact link target of a symlink entry, which is compared instead of a hash.
+    pub target: Option<String>,
+}
+
+impl FileEntry {
+    /// Creates an entry with only a hash, hashed with the manifest-wide algorithm.
+    ///
+    /// Args:
+    /// - `hash`: The hexadecimal digest.
+    pub fn new(hash: impl Into<String>) -> Self {
+        FileEntry {
+            hash: Some(hash.into()),
+            algorithm: None,
+            size: None,
+            mtime: None,
+            mode: None,
+            uid: None,
+            gid: None,
+            kind: None,
+            target: None,
+        }
+    }
+
+    /// Creates a directory entry, which expects a directory to exist at its path.
+    pub fn directory() -> Self {
+        FileEntry { hash: None, kind: Some(EntryType::Directory), ..FileEntry::new("") }
+    }
+
+    /// Creates a symlink entry that expects the given link target.
+    ///
+    /// Args:
+    /// - `target`: The exact link target.
+    pub fn symlink(target: impl Into<String>) -> Self {
+        FileEntry { hash: None, kind: Some(EntryType::Symlink), target: Some(target.into()), ..FileEntry::new("") }
+    }
+
+    /// Returns `true` if the entry records anything besides its hash and algorithm.
+    fn has_details(&self) -> bool {
+        self.hash.is_none()
+            || self.size.is_some()
+            || self.mtime.is_some()
+            || self.mode.is_some()
+            || self.uid.is_some()
+            || self.gid.is_some()
+            || self.kind.is_some()
+    }
+}
+
+/// The forms a file entry may take in the manifest JSON.
+#[derive(Serialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)] // Only ever lives briefly while an entry is (de)serialized.
+enum RawFileEntry {
+    Digest(String),
+    Detailed(RawDetails),
+}
+
+/// The fields of an entry written as an object.
+#[derive(Deserialize, Serialize)]
+struct RawDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    algorithm: Option<HashAlgorithm>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha512: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha1: Option<String>,
+    #[serde(default, rename = "sha3-256", skip_serializing_if = "Option::is_none")]
+    sha3_256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    blake3: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mtime: Option<u64>,
+    #[serde(default, with = "octal_mode", skip_serializing_if = "Option::is_none")]
+    mode: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gid: Option<u32>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    kind: Option<EntryType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for RawFileEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = RawFileEntry;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a hex digest or an object with a digest field")
+            }
+
+            fn visit_str<E: de::Error>(self, digest: &str) -> std::result::Result<Self::Value, E> {
+                Ok(RawFileEntry::Digest(digest.to_string()))
+            }
+
+            // The form is picked by the value's type instead of trying each in turn, so an error
+            // inside an object, such as an invalid `mode`, is reported as it is.
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> std::result::Result<Self::Value, A::Error> {
+                RawDetails::deserialize(MapAccessDeserializer::new(map)).map(RawFileEntry::Detailed)
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+impl TryFrom<RawFileEntry> for FileEntry {
+    type Error = String;
+
+    fn try_from(raw: RawFileEntry) -> std::result::Result<Self, Self::Error> {
+        let mut entry = FileEntry::new(String::new());
+
+        let (digest, algorithm) = match raw {
+            RawFileEntry::Digest(digest) => (digest, None),
+            RawFileEntry::Detailed(RawDetails { hash, algorithm, sha256, sha512, sha1, sha3_256, blake3, size, mtime, mode, uid, gid, kind, target }) => {
+                entry.size  = size;
+                entry.mtime = mtime;
+                entry.mode  = mode;
+                entry.uid  = uid;
+                entry.gid  = gid;
+                entry.kind = kind;
+
+                // Exactly one digest field is allowed, and `algorithm` only goes with `hash`.
+                let named_digests = [
+                    (HashAlgorithm::Sha256, sha256),
+                    (HashAlgorithm::Sha512, sha512),
+                    (HashAlgorithm::Sha1, sha1),
+                    (HashAlgorithm::Sha3_256, sha3_256),
+                    (HashAlgorithm::Blake3, blake3),
+                ];
+                let mut digests: Vec<(Option<HashAlgorithm>, String)> = named_digests
+                    .into_iter()
+                    .filter_map(|(named_algorithm, digest)| digest.map(|digest| (Some(named_algorithm), digest)))
+                    .collect();
+
+                if !digests.is_empty() && algorithm.is_some() {
+                    return Err("`algorithm` can only be given together with `hash`".to_string());
+                }
+                if let Some(hash) = hash {
+                    digests.push((algorithm, hash));
+                }
+
+                // Symlink entries are identified by their target and have no digest.
+                if let Some(target) = target {
+                    if !digests.is_empty() {
+                        return Err("symlink entries have a `target` instead of a digest".to_string());
+                    }
+                    if kind.is_some_and(|kind| kind != EntryType::Symlink) {
+                        return Err("only symlink entries can have a `target`".to_string());
+                    }
+
+                    entry.hash   = None;
+                    entry.kind   = Some(EntryType::Symlink);
+                    entry.target = Some(target);
+                    return Ok(entry);
+                }
+                // Directory entries only record that the directory exists, and optionally its metadata.
+                if kind == Some(EntryType::Directory) {
+                    if !digests.is_empty() {
+                        return Err("directory entries have no digest".to_string());
+                    }
+                    if size.is_some() {
+                        return Err("directory entries have no size".to_string());
+                    }
+                    if mtime.is_some() {
+                        return Err("directory entries have no mtime".to_string());
+                    }
+
+                    entry.hash = None;
+                    return Ok(entry);
+                }
+                if kind == Some(EntryType::Symlink) && digests.is_empty() {
+                    return Err("symlink entries need a `target`".to_string());
+                }
+                if digests.len() != 1 {
+                    return Err(format!("expected exactly one digest field, found {}", digests.len()));
+                }
+
+                let (algorithm, digest) = digests.remove(0);
+                (digest, algorithm)
+            }
+        };
+
+        // A known `algorithm:` prefix on the digest names the algorithm of the entry.
+        if let Some((prefix, hash)) = digest.split_once(':') {
+            if let Ok(prefixed_algorithm) = prefix.parse::<HashAlgorithm>() {
+                entry.hash      = Some(hash.to_string());
+                entry.algorithm = Some(prefixed_algorithm);
+                return Ok(entry);
+            }
+        }
+
+        entry.hash      = Some(digest);
+        entry.algorithm = algorithm;
+        Ok(entry)
+    }
+}
+
+impl From<FileEntry> for RawFileEntry {
+    fn from(entry: FileEntry) -> Self {
+        if let (false, Some(hash)) = (entry.has_details(), &entry.hash) {
+            return match entry.algorithm {
+                Some(algorithm) => RawFileEntry::Digest(format!("{}:{}", algorithm, hash)),
+                None => RawFileEntry::Digest(hash.clone()),
+            };
+        }
+
+        // Entries with details are written as objects, keyed by their algorithm if they name one.
+        let digest_for = |algorithm: HashAlgorithm| entry.hash.clone().filter(|_| entry.algorithm == Some(algorithm));
+
+        RawFileEntry::Detailed(RawDetails {
+            hash: entry.hash.clone().filter(|_| entry.algorithm.is_none()),
+            algorithm: None,
+            sha256: digest_for(HashAlgorithm::Sha256),
+            sha512: digest_for(HashAlgorithm::Sha512),
+            sha1: digest_for(HashAlgorithm::Sha1),
+            sha3_256: digest_for(HashAlgorithm::Sha3_256),
+            blake3: digest_for(HashAlgorithm::Blake3),
+            size: entry.size,
+            mtime: entry.mtime,
+            mode: entry.mode,
+            uid: entry.uid,
+            gid: entry.gid,
+            kind: entry.kind,
+            target: entry.target,
+        })
+    }
+}
+
+/// Reads the specified manifest file and parses it into a `Manifest` struct, validating its entries
+/// and checking its embedded signature against the trust policy.
+///
+/// The format is detected from the file's extension, or failing that from its contents, see
+/// `ManifestFormat::detect`. The manifest remembers its format so it is written back the same way.
+///
+/// Manifest paths use forward slashes. Paths written with backslashes, as on Windows, are
+/// normalized to forward slashes and reported in the manifest's `warnings`, except in checksum
+/// lists read on Unix, where a backslash is part of the file name.
+///
+/// Args:
+/// - `manifest_path`: The path to the manifest file.
+/// - `policy`: The trusted keys and whether a signature is required. The default policy accepts
+///   any manifest without checking signatures.
+///
+/// Returns:
+/// - `Result<Manifest>`: The parsed manifest or an error if reading, parsing, validation or the
+///   signature check failed.
+pub fn read_manifest(manifest_path: &Path, policy: &TrustPolicy) -> Result<Manifest>
+{
+    read_manifest_as(manifest_path, None, policy)
+}
+
+/// Reads a manifest like `read_manifest`, in the given format instead of a detected one.
+///
+/// Args:
+/// - `manifest_path`: The path to the manifest file.
+/// - `format`: The format the manifest is written in, `None` to detect it.
+/// - `policy`: The trusted keys and whether a signature is required.
+///
+/// Returns:
+/// - `Result<Manifest>`: The parsed manifest or an error if reading, parsing, validation or the
+///   signature check failed.
+pub fn read_manifest_as(manifest_path: &Path, format: Option<ManifestFormat>, policy: &TrustPolicy) -> Result<Manifest>
+{
+    let contents: String = fs::read_to_string(manifest_path).map_err(|source| Error::manifest(manifest_path, format!("cannot open manifest: {}", source)))?;
+
+    parse_manifest(manifest_path, &contents, format, policy)
+}
+
+/// Parses a manifest that was already read, such as one stored inside an archive, then checks
+/// its embedded signature and validates it like `read_manifest_as`.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest was read from, used to detect its format and in
+///   error messages.
+/// - `contents`: The manifest text.
+/// - `format`: The format the manifest is written in, `None` to detect it.
+/// - `policy`: The trusted keys and whether a signature is required.
+///
+/// Returns:
+/// - `Result<Manifest>`: The parsed manifest or an error if parsing, validation or the signature
+///   check failed.
+pub fn parse_manifest(manifest_path: &Path, contents: &str, format: Option<ManifestFormat>, policy: &TrustPolicy) -> Result<Manifest>
+{
+    let format: ManifestFormat = format.unwrap_or_else(|| ManifestFormat::detect(manifest_path, contents));
+
+    let mut manifest: Manifest = match format {
+        ManifestFormat::Json      => parse_json(manifest_path, contents)?,
+        ManifestFormat::Yaml      => parse_yaml(manifest_path, contents)?,
+        ManifestFormat::Toml      => parse_toml(manifest_path, contents)?,
+        ManifestFormat::Sha256sum => parse_checksums(manifest_path, contents)?,
+    };
+    manifest.format = format;
+
+    // The signature covers the manifest as written, before its paths are normalized. A checksum
+    // list escapes the backslashes in its paths, so on Unix they belong to the file name.
+    check_embedded_signature(manifest_path, &manifest, policy)?;
+    if !(cfg!(unix) && format == ManifestFormat::Sha256sum) {
+        normalize_separators(manifest_path, &mut manifest)?;
+    }
+    manifest.validate(manifest_path)?;
+
+    Ok(manifest)
+}
+
+/// Replaces backslash separators in manifest paths with forward slashes, keeping the entry order,
+/// and adds a warning if any path used them.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest was read from, used in messages.
+/// - `manifest`: The manifest to normalize.
+///
+/// Returns:
+/// - `Result<()>`: Ok, or an error naming an entry that becomes the same path as another entry.
+fn normalize_separators(manifest_path: &Path, manifest: &mut Manifest) -> Result<()> {
+    let Some(example) = manifest.files.keys().find(|key| key.contains('\\')).cloned() else {
+        return Ok(());
+    };
+
+    let mut files: IndexMap<String, FileEntry> = IndexMap::with_capacity(manifest.files.len());
+    let mut normalized_count = 0;
+
+    for (key, entry) in &manifest.files {
+        let path = key.replace('\\', "/");
+        if files.contains_key(&path) {
+            return Err(Error::InvalidEntry {
+                path: manifest_path.to_path_buf(),
+                key: key.clone(),
+                reason: format!("path is listed twice, as \"{}\"", path),
+            });
+        }
+
+        normalized_count += usize::from(path != *key);
+        files.insert(path, entry.clone());
+    }
+
+    manifest.files = files;
+    manifest.warnings.push(format!(
+        "{}: {} path(s) use backslash separators and were normalized to forward slashes, such as \"{}\"",
+        manifest_path.display(),
+        normalized_count,
+        example
+    ));
+
+    Ok(())
+}
+
+/// Parses manifest JSON, reporting the line, column and key of the first problem.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest is read from, used in error messages.
+/// - `contents`: The manifest JSON.
+///
+/// Returns:
+/// - `Result<Manifest>`: The parsed manifest, or a parse error.
+fn parse_json(manifest_path: &Path, contents: &str) -> Result<Manifest> {
+    let mut deserializer = serde_json::Deserializer::from_str(contents);
+
+    let parse_error = |error: &serde_json::Error, key: Option<String>| {
+        // The location is reported separately, so drop serde_json's own suffix from the message.
+        let message  = error.to_string();
+        let location = format!(" at line {} column {}", error.line(), error.column());
+
+        Error::Parse {
+            path: manifest_path.to_path_buf(),
+            line: error.line(),
+            column: error.column(),
+            key,
+            message: message.strip_suffix(&location).unwrap_or(&message).to_string(),
+        }
+    };
+
+    let manifest: Manifest = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|error| parse_error(error.inner(), error_key(error.path())))?;
+
+    // Reject anything but whitespace after the manifest object.
+    deserializer.end().map_err(|error| parse_error(&error, None))?;
+
+    Ok(manifest)
+}
+
+/// Parses a YAML manifest, reporting the line, column and key of the first problem.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest is read from, used in error messages.
+/// - `contents`: The manifest YAML.
+///
+/// Returns:
+/// - `Result<Manifest>`: The parsed manifest, or a parse error.
+fn parse_yaml(manifest_path: &Path, contents: &str) -> Result<Manifest> {
+    let deserializer = serde_yaml::Deserializer::from_str(contents);
+
+    serde_path_to_error::deserialize(deserializer).map_err(|error| {
+        let inner    = error.inner();
+        let location = inner.location();
+        let key      = error_key(error.path());
+
+        // The location is reported separately, so drop serde_yaml's own suffix from the message.
+        let message = inner.to_string();
+        let message = match &location {
+            Some(location) => {
+                let suffix = format!(" at line {} column {}", location.line(), location.column());
+                message.strip_suffix(&suffix).unwrap_or(&message).to_string()
+            }
+            None => message,
+        };
+
+        // serde_yaml also starts the message with the part of the key it tracks itself.
+        let message = match (message.split_once(": "), &key) {
+            (Some((prefix, rest)), Some(key)) if key.starts_with(prefix) => rest.to_string(),
+            _ => message,
+        };
+
+        Error::Parse {
+            path: manifest_path.to_path_buf(),
+            line: location.as_ref().map_or(0, |location| location.line()),
+            column: location.as_ref().map_or(0, |location| location.column()),
+            key,
+            message,
+        }
+    })
+}
+
+/// Parses a TOML manifest, reporting the line, column and key of the first problem.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest is read from, used in error messages.
+/// - `contents`: The manifest TOML.
+///
+/// Returns:
+/// - `Result<Manifest>`: The parsed manifest, or a parse error.
+fn parse_toml(manifest_path: &Path, contents: &str) -> Result<Manifest> {
+    let deserializer = toml::Deserializer::new(contents);
+
+    serde_path_to_error::deserialize(deserializer).map_err(|error| {
+        let inner          = error.inner();
+        let (line, column) = inner.span().map_or((0, 0), |span| line_and_column(contents, span.start));
+
+        Error::Parse {
+            path: manifest_path.to_path_buf(),
+            line,
+            column,
+            key: error_key(error.path()),
+            // Some messages list the expected tokens on further lines.
+            message: inner.message().trim().replace('\n', ", "),
+        }
+    })
+}
+
+/// Returns the key a parse error is at, or `None` for the root, which is rendered as ".", and for
+/// errors outside any value, such as an unexpected end of file, which are rendered as "?".
+fn error_key(path: &serde_path_to_error::Path) -> Option<String> {
+    Some(path.to_string()).filter(|key| key != "." && key != "?")
+}
+
+/// Returns the 1-based line and column of a byte offset in a text.
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before     = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+/// Serializes a manifest in its format. JSON is written with two-space indentation and a trailing
+/// newline, YAML and TOML in their usual block style. Entries are written in the manifest's order,
+/// so a canonical manifest always serializes to the same bytes.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest is written to, used in error messages.
+/// - `manifest`: The manifest to serialize.
+///
+/// Returns:
+/// - `Result<String>`: The serialized manifest, or an error if its format can't hold it.
+pub fn serialize_manifest(manifest_path: &Path, manifest: &Manifest) -> Result<String> {
+    match manifest.format {
+        ManifestFormat::Json => {
+            // Serializing strings, numbers and enums into memory cannot fail.
+            let mut text = to_string_pretty(manifest).expect("manifest is serializable");
+            text.push('\n');
+            Ok(text)
+        }
+        ManifestFormat::Yaml => {
+            serde_yaml::to_string(manifest).map_err(|error| Error::manifest(manifest_path, format!("cannot write YAML: {}", error)))
+        }
+        ManifestFormat::Toml => {
+            toml::to_string_pretty(manifest).map_err(|error| Error::manifest(manifest_path, format!("cannot write TOML: {}", error)))
+        }
+        ManifestFormat::Sha256sum => serialize_checksums(manifest_path, manifest),
+    }
+}
+
+/// Serializes a manifest in its format and writes it to the specified file, see `serialize_manifest`.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest file is written to.
+/// - `manifest`: The manifest to write.
+///
+/// Returns:
+/// - `Result<()>`: Ok if the manifest was written, or an error if its format can't hold it or
+///   writing failed.
+pub fn write_manifest(manifest_path: &Path, manifest: &Manifest) -> Result<()>
+{
+    fs::write(manifest_path, serialize_manifest(manifest_path, manifest)?).map_err(|source| Error::io(manifest_path, source))
+}
+
+/// Options controlling how a manifest is generated.
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    /// The hash algorithm to record the files with.
+    pub algorithm: HashAlgorithm,
+    /// How symlinks inside the directory are treated, `Record` by default so links are written as
+    /// symlink entries instead of hashed like the files they point to.
+    pub symlinks: SymlinkPolicy,
+    /// Whether the size of each file is recorded next to its hash.
+    pub record_sizes: bool,
+    /// Whether the size and modification time of each file are recorded, so `update` can skip
+    /// files that haven't changed.
+    pub record_mtimes: bool,
+    /// Whether the type, permission bits and ownership of each file are recorded.
+    pub record_metadata: bool,
+    /// Which paths are recorded, on top of the directory's `.manifestignore` file.
+    pub paths: PathRules,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            algorithm: HashAlgorithm::default(),
+            symlinks: SymlinkPolicy::Record,
+            record_sizes: false,
+            record_mtimes: false,
+            record_metadata: false,
+            paths: PathRules::default(),
+        }
+    }
+}
+
+/// Walks the directory and records the hash of every file it contains, and every empty directory
+/// so the layout can be reproduced. Paths left out by `options.paths` or the directory's
+/// `.manifestignore` file are not recorded.
+///
+/// Args:
+/// - `directory_path`: Path to the directory containing the files to record.
+/// - `options`: The hash algorithm, symlink policy, which paths and which details to record.
+///
+/// Returns:
+/// - `Result<Manifest>`: The generated manifest, or an error if walking or hashing failed, or a
+///   symlink is rejected by the symlink policy.
+pub fn generate_manifest(directory_path: &Path, options: &GenerateOptions) -> Result<Manifest> {
+    let mut files: IndexMap<String, FileEntry> = IndexMap::new();
+
+    let filter            = PathFilter::new(directory_path, &options.paths, &[])?;
+    let file_paths        = list_directory_files(directory_path, options.symlinks, &filter)?;
+    let empty_directories = list_empty_directories(directory_path, &filter)?;
+
+    for relative_path in file_paths.into_iter().chain(empty_directories) {
+        if let Some(entry) = record_entry(directory_path, &relative_path, options)? {
+            files.insert(relative_path, entry);
+        }
+    }
+
+    // Generated manifests are canonical: keys are already normalized and hashes lowercase.
+    files.sort_keys();
+
+    // SHA256 manifests, and manifests whose entries name their algorithm, need no `algorithm` field.
+    let detailed  = options.record_sizes || options.record_mtimes || options.record_metadata;
+    let algorithm = (options.algorithm != HashAlgorithm::Sha256 && !detailed).then_some(options.algorithm);
+
+    Ok(Manifest {
+        algorithm,
+        files,
+        ignore: Vec::new(),
+        signature: None,
+        extra: IndexMap::new(),
+        format: ManifestFormat::Json,
+        warnings: Vec::new(),
+    })
+}
+
+/// Returns the modification time of a file in nanoseconds since the Unix epoch.
+///
+/// Args:
+/// - `metadata`: The metadata of the file.
+///
+/// Returns:
+/// - `Option<u64>`: The modification time, or `None` if the platform doesn't record it or it is
+///   before the epoch.
+pub(crate) fn modified_nanos(metadata: &fs::Metadata) -> Option<u64> {
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    u64::try_from(since_epoch.as_nanos()).ok()
+}
+
+/// Records the entry for a single path in the directory, as generation does.
+///
+/// Args:
+/// - `directory_path`: Path to the directory containing the path.
+/// - `relative_path`: A normalized manifest path.
+/// - `options`: The hash algorithm, symlink policy and which details to record.
+///
+/// Returns:
+/// - `Result<Option<FileEntry>>`: The entry, `None` if the path doesn't exist, or an error if
+///   hashing failed or the path is rejected by the symlink policy.
+pub(crate) fn record_entry(directory_path: &Path, relative_path: &str, options: &GenerateOptions) -> Result<Option<FileEntry>> {
+    let file_path = directory_path.join(relative_path);
+
+    // Recorded symlinks keep their target instead of a hash of whatever it points to.
+    let mut entry = match resolve_entry(directory_path, relative_path, options.symlinks)
+        .map_err(|source| Error::io(&file_path, source))?
+    {
+        ResolvedPath::File(resolved_path) => {
+            // The metadata is read before hashing, so a file changed while it is hashed looks stale.
+            let metadata  = fs::metadata(&resolved_path).map_err(|source| Error::io(&resolved_path, source))?;
+            let mut entry = FileEntry::new(hash_file(&resolved_path, options.algorithm)?);
+            if options.record_sizes || options.record_mtimes {
+                entry.size = Some(metadata.len());
+            }
+            if options.record_mtimes {
+                entry.mtime = modified_nanos(&metadata);
+            }
+            entry
+        }
+        ResolvedPath::Symlink(target) => FileEntry::symlink(target),
+        ResolvedPath::Directory => FileEntry::directory(),
+        ResolvedPath::Unsafe(reason) => return Err(Error::unsafe_path(&file_path, reason)),
+        ResolvedPath::Missing => return Ok(None),
+    };
+
+    if options.record_metadata {
+        let metadata = read_metadata(&file_path).map_err(|source| Error::io(&file_path, source))?;
+        entry.mode = metadata.mode;
+        entry.uid  = metadata.uid;
+        entry.gid  = metadata.gid;
+        entry.kind = Some(metadata.kind);
+    }
+
+    // Entries written as objects name their algorithm themselves, as in `{ "sha256": "…", "size": 123 }`.
+    if entry.hash.is_some() && entry.has_details() {
+        entry.algorithm = Some(options.algorithm);
+    }
+
+    Ok(Some(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    /// Reads an entry from JSON, panicking with the reason if it is rejected.
+    fn entry(value: serde_json::Value) -> FileEntry {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// Returns why an entry is rejected.
+    fn entry_error(value: serde_json::Value) -> String {
+        serde_json::from_value::<FileEntry>(value).unwrap_err().to_string()
+    }
+
+    /// Returns the reason validation gives for the first invalid entry of a manifest listing `key`.
+    fn validation_error(key: &str) -> Option<String> {
+        let manifest = Manifest {
+            algorithm: None,
+            files: IndexMap::from([(key.to_string(), FileEntry::new(SHA256))]),
+            ignore: Vec::new(),
+            signature: None,
+            extra: IndexMap::new(),
+            format: ManifestFormat::Json,
+            warnings: Vec::new(),
+        };
+
+        match manifest.validate(Path::new("manifest.json")) {
+            Ok(()) => None,
+            Err(Error::InvalidEntry { reason, .. }) => Some(reason),
+            Err(error) => panic!("unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn validate_rejects_absolute_paths() {
+        for key in ["/etc/passwd", "\\Windows\\System32", "C:/Windows", "c:relative"] {
+            assert_eq!(validation_error(key).as_deref(), Some("path must be relative to the verified directory"), "{}", key);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_paths_escaping_the_directory() {
+        for key in ["..", "../outside.txt", "a/../../outside.txt"] {
+            assert_eq!(validation_error(key).as_deref(), Some("path escapes the verified directory"), "{}", key);
+        }
+        assert_eq!(validation_error("").as_deref(), Some("path is empty"));
+        assert_eq!(validation_error("a/..").as_deref(), Some("path names the verified directory itself"));
+    }
+
+    #[test]
+    fn validate_accepts_relative_paths_inside_the_directory() {
+        for key in ["file.txt", "a/../b/file.txt", "./a/file.txt", "dir:name/file.txt"] {
+            assert_eq!(validation_error(key), None, "{}", key);
+        }
+    }
+
+    #[test]
+    fn generation_leaves_out_a_manifest_written_into_the_directory() {
+        let directory     = tempfile::tempdir().unwrap();
+        let manifest_path = directory.path().join("MANIFEST.json");
+        fs::write(directory.path().join("a.txt"), "a").unwrap();
+
+        let mut options = GenerateOptions::default();
+        options.paths.exclude_manifest(directory.path(), &manifest_path);
+
+        let first = generate_manifest(directory.path(), &options).unwrap();
+        write_manifest(&manifest_path, &first).unwrap();
+        fs::write(directory.path().join("MANIFEST.json.sig"), "signature").unwrap();
+
+        let second = generate_manifest(directory.path(), &options).unwrap();
+        assert_eq!(second.files.keys().collect::<Vec<_>>(), ["a.txt"]);
+        assert_eq!(serialize_manifest(&manifest_path, &second).unwrap(), fs::read_to_string(&manifest_path).unwrap());
+    }
+
+    #[test]
+    fn only_manifests_inside_the_directory_are_excluded() {
+        let parent    = tempfile::tempdir().unwrap();
+        let directory = parent.path().join("d");
+        fs::create_dir(&directory).unwrap();
+
+        let mut rules = PathRules::default();
+        rules.exclude_manifest(&directory, &parent.path().join("MANIFEST.json"));
+        rules.exclude_manifest(&directory, &directory.join("../d2/MANIFEST.json"));
+        assert!(rules.exclude.is_empty());
+
+        fs::create_dir(directory.join("sub")).unwrap();
+        rules.exclude_manifest(&directory, &directory.join("sub/MANIFEST.json"));
+        assert_eq!(rules.exclude, ["/sub/MANIFEST.json", "/sub/MANIFEST.json.sig"]);
+    }
+
+    /// Parses a JSON manifest, canonicalizes it and returns whether its signature was removed,
+    /// along with its serialized form.
+    fn canonicalized(contents: &str) -> (bool, String) {
+        let manifest_path = Path::new("manifest.json");
+        let mut manifest  = parse_manifest(manifest_path, contents, None, &TrustPolicy::default()).unwrap();
+        let removed       = manifest.canonicalize(manifest_path).unwrap();
+
+        (removed, serialize_manifest(manifest_path, &manifest).unwrap())
+    }
+
+    #[test]
+    fn canonicalizing_twice_gives_the_same_bytes() {
+        let contents = format!(
+            r#"{{"zeta": 1, "files": {{"./b.txt": "{}", "a\\c.txt": "{}"}}, "alpha": {{"y": 2, "x": 1}}}}"#,
+            SHA256.to_ascii_uppercase(),
+            SHA256
+        );
+
+        let (_, once)  = canonicalized(&contents);
+        let (_, twice) = canonicalized(&once);
+        assert_eq!(once, twice);
+
+        let expected = format!(
+            "{{\n  \"files\": {{\n    \"a/c.txt\": \"{0}\",\n    \"b.txt\": \"{0}\"\n  }},\n  \"alpha\": {{\n    \"x\": 1,\n    \"y\": 2\n  }},\n  \"zeta\": 1\n}}\n",
+            SHA256
+        );
+        assert_eq!(once, expected);
+    }
+
+    #[test]
+    fn canonicalizing_removes_an_embedded_signature_only_if_what_it_covers_changed() {
+        let signature = r#""signature": {"algorithm": "ed25519", "key_id": "0011223344556677", "signature": "c2ln"}"#;
+
+        let canonical = format!(r#"{{"files": {{"a.txt": "{}"}}, "zeta": 1, "alpha": 2, {}}}"#, SHA256, signature);
+        let (removed, text) = canonicalized(&canonical);
+        assert!(!removed, "sorting keys doesn't change the signed bytes");
+        assert!(text.contains("\"signature\""));
+
+        let uppercase = format!(r#"{{"files": {{"a.txt": "{}"}}, {}}}"#, SHA256.to_ascii_uppercase(), signature);
+        let (removed, text) = canonicalized(&uppercase);
+        assert!(removed);
+        assert!(!text.contains("\"signature\""));
+    }
+
+    #[test]
+    fn plain_digests_round_trip() {
+        let plain = entry(json!(SHA256));
+        assert_eq!(plain, FileEntry::new(SHA256));
+        assert_eq!(serde_json::to_value(&plain).unwrap(), json!(SHA256));
+    }
+
+    #[test]
+    fn prefixed_digests_name_their_algorithm() {
+        let prefixed = entry(json!(format!("blake3:{}", SHA256)));
+        assert_eq!(prefixed.hash.as_deref(), Some(SHA256));
+        assert_eq!(prefixed.algorithm, Some(HashAlgorithm::Blake3));
+        assert_eq!(serde_json::to_value(&prefixed).unwrap(), json!(format!("blake3:{}", SHA256)));
+
+        // An unknown prefix is part of the digest, which validation then rejects.
+        assert_eq!(entry(json!("md5:abcd")).hash.as_deref(), Some("md5:abcd"));
+    }
+
+    #[test]
+    fn hash_with_algorithm_is_written_as_a_prefixed_digest() {
+        let detailed = entry(json!({ "hash": SHA256, "algorithm": "sha3-256" }));
+        assert_eq!(detailed.algorithm, Some(HashAlgorithm::Sha3_256));
+        assert_eq!(serde_json::to_value(&detailed).unwrap(), json!(format!("sha3-256:{}", SHA256)));
+
+        let named = entry(json!({ "sha512": SHA256 }));
+        assert_eq!(named.algorithm, Some(HashAlgorithm::Sha512));
+    }
+
+    #[test]
+    fn entries_need_exactly_one_digest() {
+        assert_eq!(entry_error(json!({ "sha256": SHA256, "sha512": SHA256 })), "expected exactly one digest field, found 2");
+        assert_eq!(entry_error(json!({ "sha256": SHA256, "algorithm": "sha256" })), "`algorithm` can only be given together with `hash`");
+    }
+
+    /// Returns the error parsing a manifest fails with, its format told from the file name.
+    fn parse_error(file_name: &str, contents: &str) -> String {
+        parse_manifest(Path::new(file_name), contents, None, &TrustPolicy::default()).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parse_errors_inside_an_entry_name_the_field() {
+        let json = format!(r#"{{"files": {{"a.txt": {{"hash": "{}", "mode": "9z"}}}}}}"#, SHA256);
+        let yaml = format!("files:\n  a.txt:\n    hash: {}\n    mode: \"9z\"\n", SHA256);
+        let toml = format!("[files]\n\"a.txt\" = {{ hash = \"{}\", mode = \"9z\" }}\n", SHA256);
+
+        assert_eq!(parse_error("m.json", &json), "m.json:1:109: at `files.a.txt.mode`: mode \"9z\" is not an octal number");
+        assert_eq!(parse_error("m.yaml", &yaml), "m.yaml:4:11: at `files.a.txt.mode`: mode \"9z\" is not an octal number");
+        assert_eq!(parse_error("m.toml", &toml), "m.toml:2:95: at `files.a.txt.mode`: mode \"9z\" is not an octal number");
+    }
+
+    #[test]
+    fn parse_errors_for_entries_of_the_wrong_type_name_both_forms() {
+        assert_eq!(
+            parse_error("m.json", r#"{"files": {"a.txt": 5}}"#),
+            "m.json:1:21: at `files.a.txt`: invalid type: integer `5`, expected a hex digest or an object with a digest field"
+        );
+        assert_eq!(
+            parse_error("m.yaml", "files:\n  a.txt: 5\n"),
+            "m.yaml:2:10: at `files.a.txt`: invalid type: integer `5`, expected a hex digest or an object with a digest field"
+        );
+    }
+
+    #[test]
+    fn yaml_parse_errors_name_the_key_once() {
+        assert_eq!(parse_error("m.yaml", "files: [1\n"), "m.yaml:1:8: at `files`: invalid type: sequence, expected a map");
+        assert_eq!(
+            parse_error("m.yaml", "algorithm: md9\nfiles: {}\n"),
+            "m.yaml:1:12: at `algorithm`: unknown variant `md9`, expected one of `sha256`, `sha512`, `sha1`, `sha3-256`, `blake3`"
+        );
+    }
+
+    #[test]
+    fn parse_errors_outside_any_value_have_no_key() {
+        assert_eq!(parse_error("m.json", &format!(r#"{{"files": {{"a.txt": "{}"}}"#, SHA256)), "m.json:1:87: EOF while parsing an object");
+        assert_eq!(parse_error("m.json", r#"{"files": {}} trailing"#), "m.json:1:15: trailing characters");
+    }
+
+    #[test]
+    fn entries_with_sizes_round_trip() {
+        let object = json!({ "sha256": SHA256, "size": 12 });
+        let sized  = entry(object.clone());
+
+        assert_eq!(sized.size, Some(12));
+        assert_eq!(serde_json::to_value(sized).unwrap(), object);
+        assert_eq!(entry_error(json!({ "size": 12 })), "expected exactly one digest field, found 0");
+    }
+
+    #[test]
+    fn entries_with_metadata_round_trip() {
+        let object   = json!({ "hash": SHA256, "mode": "0755", "uid": 0, "gid": 100, "type": "file" });
+        let detailed = entry(object.clone());
+
+        assert_eq!((detailed.mode, detailed.uid, detailed.gid, detailed.kind), (Some(0o755), Some(0), Some(100), Some(EntryType::File)));
+        assert_eq!(serde_json::to_value(detailed).unwrap(), object);
+        assert_eq!(entry(json!({ "hash": SHA256, "mode": 493 })).mode, Some(0o755));
+    }
+
+    #[test]
+    fn yaml_and_toml_manifests_round_trip() {
+        let value = json!({
+            "algorithm": "sha256",
+            "files": {
+                "bin/app": { "hash": SHA256, "size": 12, "mode": "0755", "uid": 0, "gid": 100 },
+                "lib/app.so": format!("sha512:{}", "ab".repeat(64)),
+                "lib/app.so.1": { "type": "symlink", "target": "app.so" },
+                "share": { "type": "dir" },
+                "z.txt": SHA256
+            },
+            "ignore": ["*.log"],
+            "build": { "id": 42, "tags": ["release"] }
+        });
+
+        for (file_name, format) in [("manifest.yaml", ManifestFormat::Yaml), ("manifest.toml", ManifestFormat::Toml)] {
+            let mut manifest = parse_manifest(Path::new("manifest.json"), &value.to_string(), None, &TrustPolicy::default()).unwrap();
+            manifest.format  = format;
+
+            let path     = Path::new(file_name);
+            let text     = serialize_manifest(path, &manifest).unwrap();
+            let reparsed = parse_manifest(path, &text, None, &TrustPolicy::default()).unwrap();
+
+            assert_eq!(reparsed.format, format, "{}", text);
+            assert_eq!(serde_json::to_value(&reparsed).unwrap(), value, "{}", text);
+            assert_eq!(serialize_manifest(path, &reparsed).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn symlink_entries_are_identified_by_their_target() {
+        let object = json!({ "type": "symlink", "target": "libfoo.so.1" });
+
+        assert_eq!(entry(json!({ "target": "libfoo.so.1" })), FileEntry::symlink("libfoo.so.1"));
+        assert_eq!(entry(object.clone()), FileEntry::symlink("libfoo.so.1"));
+        assert_eq!(serde_json::to_value(FileEntry::symlink("libfoo.so.1")).unwrap(), object);
+    }
+
+    #[test]
+    fn malformed_symlink_entries_are_rejected() {
+        assert_eq!(entry_error(json!({ "hash": SHA256, "target": "elsewhere" })), "symlink entries have a `target` instead of a digest");
+        assert_eq!(entry_error(json!({ "type": "file", "target": "elsewhere" })), "only symlink entries can have a `target`");
+        assert_eq!(entry_error(json!({ "type": "symlink" })), "symlink entries need a `target`");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn generation_records_symlinks_that_then_verify() {
+        use crate::verify::{verify_directory, VerifyOptions};
+
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(directory.path().join("libfoo.so.1"), "library").unwrap();
+        std::os::unix::fs::symlink("libfoo.so.1", directory.path().join("libfoo.so")).unwrap();
+
+        let manifest = generate_manifest(directory.path(), &GenerateOptions::default()).unwrap();
+        assert_eq!(manifest.files["libfoo.so"], FileEntry::symlink("libfoo.so.1"));
+        assert!(manifest.files["libfoo.so.1"].hash.is_some());
+
+        let options = VerifyOptions { strict: true, ..VerifyOptions::default() };
+        assert!(verify_directory(directory.path(), &manifest, &options).unwrap().is_success());
+
+        fs::remove_file(directory.path().join("libfoo.so")).unwrap();
+        std::os::unix::fs::symlink("libfoo.so.2", directory.path().join("libfoo.so")).unwrap();
+        assert!(!verify_directory(directory.path(), &manifest, &options).unwrap().is_success());
+    }
+
+    #[test]
+    fn directory_entries_round_trip() {
+        for object in [json!({ "type": "dir" }), json!({ "type": "dir", "mode": "0750" })] {
+            let directory = entry(object.clone());
+
+            assert_eq!((directory.hash.as_deref(), directory.kind), (None, Some(EntryType::Directory)));
+            assert_eq!(serde_json::to_value(directory).unwrap(), object);
+        }
+    }
+
+    #[test]
+    fn malformed_directory_entries_are_rejected() {
+        assert_eq!(entry_error(json!({ "type": "dir", "hash": SHA256 })), "directory entries have no digest");
+        assert_eq!(entry_error(json!({ "type": "dir", "size": 0 })), "directory entries have no size");
+    }
+
+    #[test]
+    fn generation_records_empty_directories() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::create_dir_all(directory.path().join("logs/archive")).unwrap();
+        fs::create_dir(directory.path().join("bin")).unwrap();
+        fs::write(directory.path().join("bin/tool"), "tool").unwrap();
+
+        let manifest = generate_manifest(directory.path(), &GenerateOptions::default()).unwrap();
+        assert_eq!(manifest.files.keys().collect::<Vec<_>>(), ["bin/tool", "logs/archive"]);
+        assert_eq!(manifest.files["logs/archive"], FileEntry::directory());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::manifest::FileEntry;
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The kind of file system object a manifest entry describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EntryType {
+    #[serde(rename = "file")]
+    File,
+    #[serde(rename = "dir")]
+    Directory,
+    #[serde(rename = "symlink")]
+    Symlink,
+}
+
+impl EntryType {
+    /// Returns the name used for this type in manifests.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryType::File      => "file",
+            EntryType::Directory => "dir",
+            EntryType::Symlink   => "symlink",
+        }
+    }
+}
+
+impl fmt::Display for EntryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The file type, permission bits and ownership of a path, without following a final symlink.
+///
+/// Permissions and ownership are only available on Unix and are `None` elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub kind: EntryType,
+    pub mode: Option<u32>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+}
+
+/// Reads the type, permission bits and ownership of a path without following a final symlink.
+///
+/// Args:
+/// - `path`: The path to inspect.
+///
+/// Returns:
+/// - `io::Result<FileMetadata>`: The metadata, or an error if the path could not be inspected.
+pub fn read_metadata(path: &Path) -> io::Result<FileMetadata> {
+    let metadata = fs::symlink_metadata(path)?;
+    let file_type = metadata.file_type();
+
+    let kind = if file_type.is_symlink() {
+        EntryType::Symlink
+    } else if file_type.is_dir() {
+        EntryType::Directory
+    } else {
+        EntryType::File
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        Ok(FileMetadata {
+            kind,
+            mode: Some(metadata.mode() & 0o7777),
+            uid: Some(metadata.uid()),
+            gid: Some(metadata.gid()),
+        })
+    }
+
+    #[cfg(not(unix))]
+    {
+        Ok(FileMetadata { kind, mode: None, uid: None, gid: None })
+    }
+}
+
+/// Compares the type, permission bits and ownership an entry records against a path's metadata.
+/// Fields the entry doesn't record, or the platform can't report, are not compared.
+///
+/// Args:
+/// - `entry`: The manifest entry.
+/// - `actual`: The metadata of the path.
+///
+/// Returns:
+/// - `Vec<String>`: One description per mismatching field, empty if everything matches.
+pub(crate) fn metadata_mismatches(entry: &FileEntry, actual: &FileMetadata) -> Vec<String> {
+    let mut mismatches: Vec<String> = Vec::new();
+
+    if let Some(kind) = entry.kind.filter(|kind| *kind != actual.kind) {
+        mismatches.push(format!("type: expected {}, found {}", kind, actual.kind));
+    }
+    if let (Some(expected), Some(found)) = (entry.mode, actual.mode) {
+        if expected != found {
+            mismatches.push(format!("mode: expected {:04o}, found {:04o}", expected, found));
+        }
+    }
+    if let (Some(expected), Some(found)) = (entry.uid, actual.uid) {
+        if expected != found {
+            mismatches.push(format!("uid: expected {}, found {}", expected, found));
+        }
+    }
+    if let (Some(expected), Some(found)) = (entry.gid, actual.gid) {
+        if expected != found {
+            mismatches.push(format!("gid: expected {}, found {}", expected, found));
+        }
+    }
+
+    mismatches
+}
+
+/// Serializes and deserializes optional permission bits as an octal string such as `"0755"`.
+/// Plain integers are accepted on read as well.
+pub(crate) mod octal_mode {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(mode: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        match mode {
+            Some(mode) => serializer.serialize_str(&format!("{:04o}", mode)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+        struct ModeVisitor;
+
+        impl Visitor<'_> for ModeVisitor {
+            type Value = Option<u32>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an octal mode string such as \"0755\" or an integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                u32::try_from(value).map(Some).map_err(|_| E::custom("mode is out of range"))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                u32::from_str_radix(value.trim_start_matches("0o"), 8)
+                    .map(Some)
+                    .map_err(|_| E::custom(format!("mode \"{}\" is not an octal number", value)))
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+        }
+
+        deserializer.deserialize_any(ModeVisitor)
+    }
+}
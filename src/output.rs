@@ -0,0 +1,392 @@
+use crate::diff::{ChangeKind, EntryChange, ManifestDiff};
+use crate::report::{FileReport, FileStatus, VerificationReport};
+use serde::Serialize;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// The formats a verification report can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable lines for every entry that did not match.
+    Text,
+    /// A JSON document listing every entry with its status and hashes.
+    Json,
+    /// A JUnit XML test suite with one test case per entry.
+    Junit,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text"  => Ok(OutputFormat::Text),
+            "json"  => Ok(OutputFormat::Json),
+            "junit" => Ok(OutputFormat::Junit),
+            other   => Err(format!("unknown output format: {}", other)),
+        }
+    }
+}
+
+/// The JSON document written for a verification report.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    success: bool,
+    files: &'a [FileReport],
+}
+
+/// The JSON document written for a manifest diff.
+#[derive(Serialize)]
+struct JsonDiff<'a> {
+    identical: bool,
+    changes: &'a [EntryChange],
+}
+
+/// Renders a verification report in the requested format.
+///
+/// Args:
+/// - `report`: The verification report to render.
+/// - `format`: The format to render it in.
+///
+/// Returns:
+/// - `String`: The rendered report, ending with a newline unless it is empty.
+pub fn render_report(report: &VerificationReport, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text  => render_text(report),
+        OutputFormat::Json  => render_json(report),
+        OutputFormat::Junit => render_junit(report),
+    }
+}
+
+/// Renders a manifest diff in the requested format. JUnit has no form for diffs, so it is rendered
+/// as text.
+///
+/// Args:
+/// - `diff`: The manifest diff to render.
+/// - `format`: The format to render it in.
+///
+/// Returns:
+/// - `String`: The rendered diff, ending with a newline unless it is empty.
+pub fn render_diff(diff: &ManifestDiff, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text | OutputFormat::Junit => render_diff_text(diff),
+        OutputFormat::Json => {
+            let document = JsonDiff { identical: diff.is_empty(), changes: &diff.changes };
+
+            // Serializing plain strings and entries into memory cannot fail.
+            let mut output = serde_json::to_string_pretty(&document).expect("diff is serializable");
+            output.push('\n');
+            output
+        }
+    }
+}
+
+/// Renders every entry of the diff as human-readable lines, with the differing fields of changed
+/// entries indented below them.
+fn render_diff_text(diff: &ManifestDiff) -> String {
+    let mut output = String::new();
+
+    for entry in &diff.changes {
+        match entry.change {
+            ChangeKind::Added => {
+                let _ = writeln!(output, "Added:   {}", entry.path);
+            }
+            ChangeKind::Removed => {
+                let _ = writeln!(output, "Removed: {}", entry.path);
+            }
+            ChangeKind::Changed => {
+                let _ = writeln!(output, "Changed: {}", entry.path);
+                for difference in &entry.differences {
+                    let _ = writeln!(output, "  {}", difference);
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Renders every entry of the report that did not match as human-readable lines.
+fn render_text(report: &VerificationReport) -> String {
+    let mut output = String::new();
+
+    for file in &report.files {
+        match file.status {
+            FileStatus::Matched => {}
+            FileStatus::Mismatched if file.expected_hash.is_none() && file.expected_target.is_none() => {
+                let _ = writeln!(output, "Not a directory: {} ({})", file.path, file.error.as_deref().unwrap_or_default());
+            }
+            FileStatus::Mismatched if file.expected_target.is_some() => {
+                let _ = writeln!(output, "Mismatched symlink target for file: {}", file.path);
+                let _ = writeln!(output, "Expected: {}", file.expected_target.as_deref().unwrap_or_default());
+                let _ = writeln!(output, "Found:    {}", found_target(file));
+                for mismatch in &file.metadata_mismatches {
+                    let _ = writeln!(output, "Metadata: {}", mismatch);
+                }
+            }
+            FileStatus::Mismatched => {
+                let _ = writeln!(output, "Mismatched hash for file: {}", file.path);
+                let _ = writeln!(output, "Expected: {}", file.expected_hash.as_deref().unwrap_or_default());
+                let _ = writeln!(output, "Found:    {}", file.actual_hash.as_deref().unwrap_or_default());
+                for mismatch in &file.metadata_mismatches {
+                    let _ = writeln!(output, "Metadata: {}", mismatch);
+                }
+            }
+            FileStatus::MetadataMismatch => {
+                let _ = writeln!(output, "Mismatched metadata for file: {}", file.path);
+                for mismatch in &file.metadata_mismatches {
+                    let _ = writeln!(output, "  {}", mismatch);
+                }
+            }
+            FileStatus::SizeMismatch => {
+                let _ = writeln!(output, "Mismatched size for file: {}{}", file.path, truncation_note(file));
+                let _ = writeln!(output, "Expected: {} bytes", file.expected_size.unwrap_or_default());
+                let _ = writeln!(output, "Found:    {} bytes", file.actual_size.unwrap_or_default());
+            }
+            FileStatus::Missing => {
+                let _ = writeln!(output, "Missing file in directory: {}", file.path);
+            }
+            FileStatus::Extra => {
+                let _ = writeln!(output, "File not listed in manifest: {}", file.path);
+            }
+            FileStatus::Unreadable => {
+                let _ = writeln!(output, "Unreadable file: {} ({})", file.path, file.error.as_deref().unwrap_or_default());
+            }
+            FileStatus::Unsafe => {
+                let _ = writeln!(output, "Unsafe path: {} ({})", file.path, file.error.as_deref().unwrap_or_default());
+            }
+        }
+    }
+
+    output
+}
+
+/// Renders the report as a pretty-printed JSON document.
+fn render_json(report: &VerificationReport) -> String {
+    let document = JsonReport { success: report.is_success(), files: &report.files };
+
+    // Serializing plain strings and enums into memory cannot fail.
+    let mut output = serde_json::to_string_pretty(&document).expect("report is serializable");
+    output.push('\n');
+    output
+}
+
+/// Renders the report as a JUnit XML test suite, one test case per entry.
+fn render_junit(report: &VerificationReport) -> String {
+    let failures = report.files.iter().filter(|file| file.status != FileStatus::Matched).count();
+    let mut output = String::new();
+
+    let _ = writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        output,
+        r#"<testsuite name="manifest_checker" tests="{}" failures="{}">"#,
+        report.files.len(),
+        failures
+    );
+
+    for file in &report.files {
+        let path = escape_xml(&file.path);
+
+        if file.status == FileStatus::Matched {
+            let _ = writeln!(output, r#"  <testcase classname="manifest" name="{}"/>"#, path);
+            continue;
+        }
+
+        let message = match file.status {
+            FileStatus::Mismatched if file.expected_hash.is_none() && file.expected_target.is_none() => {
+                format!("type mismatch: {}", file.error.as_deref().unwrap_or_default())
+            }
+            FileStatus::Mismatched if file.expected_target.is_some() => format!(
+                "symlink target mismatch: expected {}, found {}",
+                file.expected_target.as_deref().unwrap_or_default(),
+                found_target(file)
+            ),
+            FileStatus::Mismatched => format!(
+                "hash mismatch: expected {}, found {}",
+                file.expected_hash.as_deref().unwrap_or_default(),
+                file.actual_hash.as_deref().unwrap_or_default()
+            ),
+            FileStatus::MetadataMismatch => format!("metadata mismatch: {}", file.metadata_mismatches.join("; ")),
+            FileStatus::SizeMismatch => format!(
+                "size mismatch{}: expected {} bytes, found {} bytes",
+                truncation_note(file),
+                file.expected_size.unwrap_or_default(),
+                file.actual_size.unwrap_or_default()
+            ),
+            FileStatus::Missing => "file listed in manifest is missing".to_string(),
+            FileStatus::Extra => "file is not listed in manifest".to_string(),
+            FileStatus::Unreadable => format!("file could not be read: {}", file.error.as_deref().unwrap_or_default()),
+            FileStatus::Unsafe => format!("path is unsafe: {}", file.error.as_deref().unwrap_or_default()),
+            FileStatus::Matched => unreachable!(),
+        };
+
+        let _ = writeln!(output, r#"  <testcase classname="manifest" name="{}">"#, path);
+        let _ = writeln!(output, r#"    <failure type="{}" message="{}"/>"#, file.status.as_str(), escape_xml(&message));
+        let _ = writeln!(output, "  </testcase>");
+    }
+
+    let _ = writeln!(output, "</testsuite>");
+    output
+}
+
+/// Returns a note that the file looks truncated if it is smaller than the manifest says.
+fn truncation_note(file: &FileReport) -> &'static str {
+    match (file.expected_size, file.actual_size) {
+        (Some(expected), Some(actual)) if actual < expected => " (truncated)",
+        _ => "",
+    }
+}
+
+/// Returns what was found in place of an expected symlink target: the actual target, or why the
+/// path is not a symlink.
+fn found_target(file: &FileReport) -> &str {
+    file.link_target.as_deref().or(file.error.as_deref()).unwrap_or_default()
+}
+
+/// Escapes the characters that are not allowed verbatim in XML attribute values.
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for character in value.chars() {
+        match character {
+            '&'  => escaped.push_str("&amp;"),
+            '<'  => escaped.push_str("&lt;"),
+            '>'  => escaped.push_str("&gt;"),
+            '"'  => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            other => escaped.push(other),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashAlgorithm;
+
+    /// Returns the report of a file hashed with SHA256 and found with the given hash.
+    fn hashed(path: &str, expected: &str, actual: &str) -> FileReport {
+        let status = if expected == actual { FileStatus::Matched } else { FileStatus::Mismatched };
+
+        let mut file = FileReport::new(path, status);
+        file.algorithm     = Some(HashAlgorithm::Sha256);
+        file.expected_hash = Some(expected.to_string());
+        file.actual_hash   = Some(actual.to_string());
+        file
+    }
+
+    /// Returns the report of a listed file that is gone.
+    fn missing(path: &str) -> FileReport {
+        let mut file = FileReport::new(path, FileStatus::Missing);
+        file.algorithm     = Some(HashAlgorithm::Sha256);
+        file.expected_hash = Some("aa".to_string());
+        file
+    }
+
+    #[test]
+    fn json_report_of_a_passing_run() {
+        let report = VerificationReport { files: vec![hashed("a.txt", "aa", "aa")] };
+
+        assert_eq!(
+            render_report(&report, OutputFormat::Json),
+            r#"{
+  "success": true,
+  "files": [
+    {
+      "path": "a.txt",
+      "status": "matched",
+      "algorithm": "sha256",
+      "expected_hash": "aa",
+      "actual_hash": "aa",
+      "expected_size": null,
+      "actual_size": null,
+      "metadata_mismatches": [],
+      "expected_target": null,
+      "link_target": null,
+      "error": null
+    }
+  ]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn json_report_of_a_mismatch_and_a_missing_file() {
+        let report = VerificationReport { files: vec![hashed("a.txt", "aa", "bb"), missing("b.txt")] };
+
+        assert_eq!(
+            render_report(&report, OutputFormat::Json),
+            r#"{
+  "success": false,
+  "files": [
+    {
+      "path": "a.txt",
+      "status": "mismatched",
+      "algorithm": "sha256",
+      "expected_hash": "aa",
+      "actual_hash": "bb",
+      "expected_size": null,
+      "actual_size": null,
+      "metadata_mismatches": [],
+      "expected_target": null,
+      "link_target": null,
+      "error": null
+    },
+    {
+      "path": "b.txt",
+      "status": "missing",
+      "algorithm": "sha256",
+      "expected_hash": "aa",
+      "actual_hash": null,
+      "expected_size": null,
+      "actual_size": null,
+      "metadata_mismatches": [],
+      "expected_target": null,
+      "link_target": null,
+      "error": null
+    }
+  ]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn junit_report_of_a_passing_run() {
+        let report = VerificationReport { files: vec![hashed("a.txt", "aa", "aa")] };
+
+        assert_eq!(
+            render_report(&report, OutputFormat::Junit),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="manifest_checker" tests="1" failures="0">
+  <testcase classname="manifest" name="a.txt"/>
+</testsuite>
+"#
+        );
+    }
+
+    #[test]
+    fn junit_report_of_a_mismatch_and_a_missing_file() {
+        let report = VerificationReport {
+            files: vec![hashed("a.txt", "aa", "aa"), hashed("b&c.txt", "aa", "bb"), missing("d.txt")],
+        };
+
+        assert_eq!(
+            render_report(&report, OutputFormat::Junit),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="manifest_checker" tests="3" failures="2">
+  <testcase classname="manifest" name="a.txt"/>
+  <testcase classname="manifest" name="b&amp;c.txt">
+    <failure type="mismatched" message="hash mismatch: expected aa, found bb"/>
+  </testcase>
+  <testcase classname="manifest" name="d.txt">
+    <failure type="missing" message="file listed in manifest is missing"/>
+  </testcase>
+</testsuite>
+"#
+        );
+    }
+}
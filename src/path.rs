@@ -0,0 +1,333 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// How symlinks inside the verified directory are treated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Follow symlinks, as long as they resolve to a path inside the directory.
+    #[default]
+    Follow,
+    /// Don't follow symlinks; hash the link target path instead of the content it points to.
+    Record,
+    /// Reject every symlink.
+    Forbid,
+}
+
+impl SymlinkPolicy {
+    /// All supported policies, in the order they are listed in help texts.
+    pub const ALL: [SymlinkPolicy; 3] = [SymlinkPolicy::Follow, SymlinkPolicy::Record, SymlinkPolicy::Forbid];
+
+    /// Returns the name used for this policy on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymlinkPolicy::Follow => "follow",
+            SymlinkPolicy::Record => "record",
+            SymlinkPolicy::Forbid => "forbid",
+        }
+    }
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        SymlinkPolicy::ALL
+            .into_iter()
+            .find(|policy| policy.as_str() == value)
+            .ok_or_else(|| format!("unknown symlink policy: {}", value))
+    }
+}
+
+/// What a manifest path resolves to inside the verified directory.
+#[derive(Debug)]
+pub(crate) enum ResolvedPath {
+    /// Nothing exists at the path.
+    Missing,
+    /// A file whose content should be hashed, possibly reached through followed symlinks.
+    File(PathBuf),
+    /// A symlink recorded by its target instead of being followed.
+    Symlink(String),
+    /// A directory, which has no content to hash.
+    Directory,
+    /// The path is rejected by the symlink policy or escapes the directory, for the given reason.
+    Unsafe(String),
+}
+
+/// Lexically normalizes a forward-slash manifest path, dropping `.` components and resolving `..`
+/// against the preceding component.
+///
+/// Args:
+/// - `key`: The manifest path.
+///
+/// Returns:
+/// - `Option<String>`: The normalized path, or `None` if it escapes the directory through `..`.
+pub fn normalize_manifest_path(key: &str) -> Option<String> {
+    let mut components: Vec<&str> = Vec::new();
+
+    for component in key.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop()?;
+            }
+            other => components.push(other),
+        }
+    }
+
+    Some(components.join("/"))
+}
+
+/// Resolves a normalized manifest path inside the directory, enforcing the symlink policy.
+///
+/// With `Follow`, the fully resolved path must stay inside the directory. With `Record` and
+/// `Forbid`, no symlink is followed, so only the last component may be a symlink, and `Forbid`
+/// rejects that too.
+///
+/// Args:
+/// - `root`: The directory the manifest describes.
+/// - `key`: A normalized manifest path, see `normalize_manifest_path`.
+/// - `policy`: How symlinks are treated.
+///
+/// Returns:
+/// - `io::Result<ResolvedPath>`: What the path resolves to, or an I/O error if it could not be
+///   inspected.
+pub(crate) fn resolve_entry(root: &Path, key: &str, policy: SymlinkPolicy) -> io::Result<ResolvedPath> {
+    let components: Vec<&str> = key.split('/').collect();
+    let mut current = root.to_path_buf();
+
+    for (index, component) in components.iter().enumerate() {
+        current.push(component);
+
+        let metadata = match fs::symlink_metadata(&current) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(ResolvedPath::Missing),
+            Err(error) => return Err(error),
+        };
+
+        if !metadata.file_type().is_symlink() {
+            continue;
+        }
+
+        let is_last = index + 1 == components.len();
+        match policy {
+            SymlinkPolicy::Follow => {}
+            SymlinkPolicy::Forbid => return Ok(ResolvedPath::Unsafe(format!("{} is a symlink", components[..=index].join("/")))),
+            SymlinkPolicy::Record if is_last => {
+                let target = fs::read_link(&current)?;
+                return Ok(ResolvedPath::Symlink(target.to_string_lossy().replace('\\', "/")));
+            }
+            SymlinkPolicy::Record => {
+                return Ok(ResolvedPath::Unsafe(format!("{} is a symlinked directory", components[..=index].join("/"))));
+            }
+        }
+    }
+
+    if policy == SymlinkPolicy::Follow {
+        // Resolve every symlink on the way and make sure the result is still inside the directory.
+        let resolved = match current.canonicalize() {
+            Ok(resolved) => resolved,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(ResolvedPath::Missing),
+            Err(error) => return Err(error),
+        };
+
+        if !resolved.starts_with(root.canonicalize()?) {
+            return Ok(ResolvedPath::Unsafe(format!("resolves to {}, outside the verified directory", resolved.display())));
+        }
+    }
+
+    if fs::metadata(&current)?.is_dir() {
+        return Ok(ResolvedPath::Directory);
+    }
+
+    Ok(ResolvedPath::File(current))
+}
+
+/// Finds the path on disk that a normalized manifest path names when case is ignored, for
+/// manifests built on case-insensitive file systems.
+///
+/// Each component that doesn't exist as written is matched against the names in its directory
+/// ignoring case. A component that matches no name, or several, is kept as written.
+///
+/// Args:
+/// - `root`: The directory the manifest describes.
+/// - `key`: A normalized manifest path, see `normalize_manifest_path`.
+///
+/// Returns:
+/// - `String`: The manifest path with each component spelled as on disk where possible.
+pub(crate) fn match_case(root: &Path, key: &str) -> String {
+    let mut components: Vec<String> = Vec::new();
+    let mut current = root.to_path_buf();
+
+    for component in key.split('/') {
+        let mut name = component.to_string();
+
+        if fs::symlink_metadata(current.join(component)).is_err() {
+            let lowercase = component.to_lowercase();
+            let candidates: Vec<String> = fs::read_dir(&current)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|candidate| candidate.to_lowercase() == lowercase)
+                .collect();
+
+            if let [candidate] = candidates.as_slice() {
+                name = candidate.clone();
+            }
+        }
+
+        current.push(&name);
+        components.push(name);
+    }
+
+    components.join("/")
+}
+
+/// Returns the manifest path a file would have inside the directory, if it lies below it. Both paths
+/// are resolved first, so relative paths and symlinked parents are compared by where they point.
+///
+/// Args:
+/// - `root`: The directory the manifest describes.
+/// - `path`: A path to a file, which needn't exist yet as long as its parent does.
+///
+/// Returns:
+/// - `Option<String>`: The forward-slash path relative to the directory, or `None` if the file lies
+///   outside it or either path can't be resolved.
+pub(crate) fn key_within(root: &Path, path: &Path) -> Option<String> {
+    let root   = root.canonicalize().ok()?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.canonicalize().ok()?,
+        _ => PathBuf::from(".").canonicalize().ok()?,
+    };
+    let path = parent.join(path.file_name()?);
+
+    path.strip_prefix(&root).ok().filter(|relative| !relative.as_os_str().is_empty())?;
+    Some(manifest_key(&root, &path))
+}
+
+/// Converts a path below the directory into a forward-slash manifest path.
+///
+/// Args:
+/// - `root`: The directory the manifest describes.
+/// - `path`: A path inside that directory.
+pub(crate) fn manifest_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_drops_dot_components_and_empty_segments() {
+        assert_eq!(normalize_manifest_path("a/./b//c.txt").as_deref(), Some("a/b/c.txt"));
+        assert_eq!(normalize_manifest_path("./a/").as_deref(), Some("a"));
+        assert_eq!(normalize_manifest_path(".").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn normalize_resolves_parent_components_inside_the_directory() {
+        assert_eq!(normalize_manifest_path("a/../b.txt").as_deref(), Some("b.txt"));
+        assert_eq!(normalize_manifest_path("a/b/../../c.txt").as_deref(), Some("c.txt"));
+        assert_eq!(normalize_manifest_path("a/..").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn normalize_rejects_paths_escaping_the_directory() {
+        assert_eq!(normalize_manifest_path(".."), None);
+        assert_eq!(normalize_manifest_path("../etc/passwd"), None);
+        assert_eq!(normalize_manifest_path("a/../../b"), None);
+        assert_eq!(normalize_manifest_path("a/./../.."), None);
+    }
+
+    #[test]
+    fn resolve_reports_missing_files_and_directories() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("dir")).unwrap();
+        fs::write(root.path().join("dir/file.txt"), "content").unwrap();
+
+        for policy in SymlinkPolicy::ALL {
+            assert!(matches!(resolve_entry(root.path(), "dir/file.txt", policy).unwrap(), ResolvedPath::File(path) if path == root.path().join("dir/file.txt")));
+            assert!(matches!(resolve_entry(root.path(), "dir", policy).unwrap(), ResolvedPath::Directory));
+            assert!(matches!(resolve_entry(root.path(), "dir/missing.txt", policy).unwrap(), ResolvedPath::Missing));
+            assert!(matches!(resolve_entry(root.path(), "missing/file.txt", policy).unwrap(), ResolvedPath::Missing));
+        }
+    }
+
+    /// Builds a directory with symlinks to a file inside it, to a file outside it and to a
+    /// subdirectory, returning the directory and the outside file's parent.
+    #[cfg(unix)]
+    fn symlinked_tree() -> (tempfile::TempDir, tempfile::TempDir) {
+        use std::os::unix::fs::symlink;
+
+        let root    = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("dir")).unwrap();
+        fs::write(root.path().join("dir/file.txt"), "content").unwrap();
+        fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+
+        symlink("dir/file.txt", root.path().join("inside")).unwrap();
+        symlink(outside.path().join("secret.txt"), root.path().join("escape")).unwrap();
+        symlink("../dir/../..", root.path().join("dir/up")).unwrap();
+        symlink("dir", root.path().join("linked_dir")).unwrap();
+
+        (root, outside)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follow_resolves_symlinks_inside_the_directory() {
+        let (root, _outside) = symlinked_tree();
+
+        assert!(matches!(resolve_entry(root.path(), "inside", SymlinkPolicy::Follow).unwrap(), ResolvedPath::File(_)));
+        assert!(matches!(resolve_entry(root.path(), "linked_dir/file.txt", SymlinkPolicy::Follow).unwrap(), ResolvedPath::File(_)));
+        assert!(matches!(resolve_entry(root.path(), "linked_dir", SymlinkPolicy::Follow).unwrap(), ResolvedPath::Directory));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follow_rejects_symlinks_escaping_the_directory() {
+        let (root, _outside) = symlinked_tree();
+
+        assert!(matches!(resolve_entry(root.path(), "escape", SymlinkPolicy::Follow).unwrap(), ResolvedPath::Unsafe(_)));
+        assert!(matches!(resolve_entry(root.path(), "dir/up", SymlinkPolicy::Follow).unwrap(), ResolvedPath::Unsafe(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn record_returns_link_targets_without_following_them() {
+        let (root, outside) = symlinked_tree();
+        let escape_target   = outside.path().join("secret.txt").to_string_lossy().into_owned();
+
+        assert!(matches!(resolve_entry(root.path(), "inside", SymlinkPolicy::Record).unwrap(), ResolvedPath::Symlink(target) if target == "dir/file.txt"));
+        assert!(matches!(resolve_entry(root.path(), "escape", SymlinkPolicy::Record).unwrap(), ResolvedPath::Symlink(target) if target == escape_target));
+        assert!(matches!(resolve_entry(root.path(), "dir/up", SymlinkPolicy::Record).unwrap(), ResolvedPath::Symlink(target) if target == "../dir/../.."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn record_rejects_paths_through_symlinked_directories() {
+        let (root, _outside) = symlinked_tree();
+
+        assert!(matches!(resolve_entry(root.path(), "linked_dir/file.txt", SymlinkPolicy::Record).unwrap(), ResolvedPath::Unsafe(_)));
+        assert!(matches!(resolve_entry(root.path(), "dir/up/secret.txt", SymlinkPolicy::Record).unwrap(), ResolvedPath::Unsafe(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn forbid_rejects_every_symlink() {
+        let (root, _outside) = symlinked_tree();
+
+        for key in ["inside", "escape", "dir/up", "linked_dir", "linked_dir/file.txt"] {
+            assert!(matches!(resolve_entry(root.path(), key, SymlinkPolicy::Forbid).unwrap(), ResolvedPath::Unsafe(_)), "{}", key);
+        }
+        assert!(matches!(resolve_entry(root.path(), "dir/file.txt", SymlinkPolicy::Forbid).unwrap(), ResolvedPath::File(_)));
+    }
+}
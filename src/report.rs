@@ -0,0 +1,121 @@
+use crate::hash::HashAlgorithm;
+use serde::Serialize;
+
+/// The outcome of verifying a single path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// The file exists and its hash matches the manifest.
+    Matched,
+    /// The file exists but its hash differs from the manifest.
+    Mismatched,
+    /// The file exists but its size differs from the manifest, so it was not hashed.
+    SizeMismatch,
+    /// The file's content matches, but its type, permission bits or ownership differ.
+    MetadataMismatch,
+    /// The file is listed in the manifest but not present in the directory.
+    Missing,
+    /// The file is present in the directory but not listed in the manifest.
+    Extra,
+    /// The file is listed in the manifest but could not be read.
+    Unreadable,
+    /// The path escapes the verified directory or is a symlink the symlink policy rejects.
+    Unsafe,
+}
+
+impl FileStatus {
+    /// Returns the snake_case name used for this status in machine-readable output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileStatus::Matched    => "matched",
+            FileStatus::Mismatched => "mismatched",
+            FileStatus::SizeMismatch => "size_mismatch",
+            FileStatus::MetadataMismatch => "metadata_mismatch",
+            FileStatus::Missing    => "missing",
+            FileStatus::Extra      => "extra",
+            FileStatus::Unreadable => "unreadable",
+            FileStatus::Unsafe     => "unsafe",
+        }
+    }
+}
+
+/// The verification result for a single path.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    /// Forward-slash path relative to the verified directory.
+    pub path: String,
+    /// What verification found for this path.
+    pub status: FileStatus,
+    /// The algorithm the file is hashed with, if the path is listed in the manifest.
+    pub algorithm: Option<HashAlgorithm>,
+    /// The hash recorded in the manifest, if the path is listed there.
+    pub expected_hash: Option<String>,
+    /// The hash computed from the file, if it could be read.
+    pub actual_hash: Option<String>,
+    /// The size recorded in the manifest, if the entry has one.
+    pub expected_size: Option<u64>,
+    /// The size of the file, if the entry records a size and the file exists.
+    pub actual_size: Option<u64>,
+    /// The type, permission bit and ownership differences, one description per field.
+    pub metadata_mismatches: Vec<String>,
+    /// The link target recorded in the manifest, for symlink entries.
+    pub expected_target: Option<String>,
+    /// The target of a symlink that was recorded instead of followed.
+    pub link_target: Option<String>,
+    /// Why the file could not be read or was rejected, for unreadable and unsafe entries.
+    pub error: Option<String>,
+    /// The number of bytes read to hash the file, zero if it wasn't read. Left out of the JSON
+    /// report, it feeds throughput statistics.
+    #[serde(skip)]
+    pub hashed_bytes: u64,
+}
+
+impl FileReport {
+    /// Creates a report for a path with the given status and no further details.
+    ///
+    /// Args:
+    /// - `path`: Forward-slash path relative to the verified directory.
+    /// - `status`: What verification found for this path.
+    pub fn new(path: impl Into<String>, status: FileStatus) -> Self {
+        FileReport {
+            path: path.into(),
+            status,
+            algorithm: None,
+            expected_hash: None,
+            actual_hash: None,
+            expected_size: None,
+            actual_size: None,
+            metadata_mismatches: Vec::new(),
+            expected_target: None,
+            link_target: None,
+            error: None,
+            hashed_bytes: 0,
+        }
+    }
+}
+
+/// The collected results of verifying a directory against a manifest, sorted by path.
+#[derive(Debug, Clone, Default)]
+pub struct VerificationReport {
+    pub files: Vec<FileReport>,
+}
+
+impl VerificationReport {
+    /// Returns `true` if every entry in the report matched.
+    pub fn is_success(&self) -> bool {
+        self.files.iter().all(|file| file.status == FileStatus::Matched)
+    }
+
+    /// Returns the total number of bytes read to hash the files in the report.
+    pub fn hashed_bytes(&self) -> u64 {
+        self.files.iter().map(|file| file.hashed_bytes).sum()
+    }
+
+    /// Returns an iterator over the entries with the given status.
+    ///
+    /// Args:
+    /// - `status`: The status to filter by.
+    pub fn with_status(&self, status: FileStatus) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(move |file| file.status == status)
+    }
+}
@@ -0,0 +1,320 @@
+use crate::error::{Error, Result};
+use crate::manifest::{read_manifest, write_manifest, Manifest};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A signature embedded in the manifest JSON, covering the canonical form of the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ManifestSignature {
+    /// The signature algorithm, always `ed25519`.
+    pub algorithm: String,
+    /// Identifies the public key the manifest was signed with, see `key_id`.
+    pub key_id: String,
+    /// The base64 encoded signature.
+    pub signature: String,
+}
+
+/// The keys an embedded manifest signature is checked against when a manifest is read.
+#[derive(Debug, Clone, Default)]
+pub struct TrustPolicy {
+    /// Public keys trusted to sign manifests. Embedded signatures are only checked if this is not empty.
+    pub trusted_keys: Vec<VerifyingKey>,
+    /// Whether manifests without an embedded signature are refused.
+    pub require_signature: bool,
+}
+
+/// The only signature algorithm supported in embedded signature blocks.
+const EMBEDDED_SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Returns the key id of a public key: the first 8 bytes of its SHA256 hash, in hex.
+///
+/// Args:
+/// - `verifying_key`: The public key to identify.
+pub fn key_id(verifying_key: &VerifyingKey) -> String {
+    let digest = Sha256::digest(verifying_key.as_bytes());
+    digest[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Returns the canonical bytes an embedded signature covers: the manifest without its signature
+/// block, serialized as compact JSON with sorted keys.
+///
+/// Args:
+/// - `manifest`: The manifest to serialize.
+///
+/// Returns:
+/// - `Vec<u8>`: The canonical bytes.
+pub fn canonical_signing_bytes(manifest: &Manifest) -> Vec<u8> {
+    // Serializing a manifest, whose map keys are all strings, into memory cannot fail.
+    let mut value = serde_json::to_value(manifest).expect("manifest is serializable");
+
+    if let Some(object) = value.as_object_mut() {
+        object.remove("signature");
+    }
+
+    serde_json::to_vec(&value).expect("manifest is serializable")
+}
+
+/// Signs a manifest with an Ed25519 private key and rewrites it with the signature embedded.
+///
+/// Args:
+/// - `manifest_path`: The path to the manifest file, which is rewritten in place.
+/// - `private_key_path`: The path to the Ed25519 private key file.
+///
+/// Returns:
+/// - `Result<()>`: Ok if the signed manifest was written, or an error.
+pub fn embed_manifest_signature(manifest_path: &Path, private_key_path: &Path) -> Result<()> {
+    let signing_key: SigningKey = read_signing_key(private_key_path)?;
+    let mut manifest: Manifest  = read_manifest(manifest_path, &TrustPolicy::default())?;
+
+    manifest.signature = None;
+    let signature: Signature = signing_key.sign(&canonical_signing_bytes(&manifest));
+
+    manifest.signature = Some(ManifestSignature {
+        algorithm: EMBEDDED_SIGNATURE_ALGORITHM.to_string(),
+        key_id: key_id(&signing_key.verifying_key()),
+        signature: BASE64.encode(signature.to_bytes()),
+    });
+
+    write_manifest(manifest_path, &manifest)
+}
+
+/// Checks a manifest's embedded signature against the trust policy.
+///
+/// Args:
+/// - `manifest_path`: The path the manifest was read from, used in error messages.
+/// - `manifest`: The manifest to check.
+/// - `policy`: The trusted keys and whether a signature is required.
+///
+/// Returns:
+/// - `Result<()>`: Ok if the manifest satisfies the policy, or an error naming why not.
+pub fn check_embedded_signature(manifest_path: &Path, manifest: &Manifest, policy: &TrustPolicy) -> Result<()> {
+    let embedded = match &manifest.signature {
+        Some(embedded) => embedded,
+        None if policy.require_signature => {
+            return Err(Error::signature(manifest_path, "manifest has no embedded signature"));
+        }
+        None => return Ok(()),
+    };
+
+    if policy.trusted_keys.is_empty() {
+        if policy.require_signature {
+            return Err(Error::signature(manifest_path, "a signature is required but no trusted keys are configured"));
+        }
+        return Ok(());
+    }
+
+    if embedded.algorithm != EMBEDDED_SIGNATURE_ALGORITHM {
+        return Err(Error::signature(manifest_path, format!("unsupported signature algorithm: {}", embedded.algorithm)));
+    }
+
+    let verifying_key = policy
+        .trusted_keys
+        .iter()
+        .find(|trusted_key| key_id(trusted_key) == embedded.key_id)
+        .ok_or_else(|| Error::signature(manifest_path, format!("manifest is signed by untrusted key {}", embedded.key_id)))?;
+
+    let signature = decode_signature(manifest_path, &embedded.signature)?;
+    verifying_key
+        .verify(&canonical_signing_bytes(manifest), &signature)
+        .map_err(|_| Error::signature(manifest_path, "embedded signature does not match the manifest"))
+}
+
+/// Returns the path of the detached signature that belongs to a manifest, `<manifest>.sig`.
+///
+/// Args:
+/// - `manifest_path`: The path to the manifest file.
+pub fn signature_path(manifest_path: &Path) -> PathBuf {
+    let mut path = manifest_path.as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// Signs the raw bytes of a manifest with an Ed25519 private key and writes the detached
+/// base64 signature next to it.
+///
+/// Args:
+/// - `manifest_path`: The path to the manifest file.
+/// - `private_key_path`: The path to the Ed25519 private key file.
+///
+/// Returns:
+/// - `Result<PathBuf>`: The path the signature was written to, or an error.
+pub fn sign_manifest(manifest_path: &Path, private_key_path: &Path) -> Result<PathBuf> {
+    let signing_key: SigningKey = read_signing_key(private_key_path)?;
+    let manifest_bytes: Vec<u8> = fs::read(manifest_path).map_err(|source| Error::io(manifest_path, source))?;
+    let signature: Signature    = signing_key.sign(&manifest_bytes);
+
+    let output_path = signature_path(manifest_path);
+    fs::write(&output_path, format!("{}\n", BASE64.encode(signature.to_bytes())))
+        .map_err(|source| Error::io(&output_path, source))?;
+
+    Ok(output_path)
+}
+
+/// Checks the detached signature of a manifest against an Ed25519 public key.
+///
+/// Args:
+/// - `manifest_path`: The path to the manifest file, whose `.sig` file is checked.
+/// - `public_key_path`: The path to the Ed25519 public key file.
+///
+/// Returns:
+/// - `Result<()>`: Ok if the signature is valid, or an error if it is missing, malformed or does
+///   not match the manifest.
+pub fn verify_manifest_signature(manifest_path: &Path, public_key_path: &Path) -> Result<()> {
+    let verifying_key: VerifyingKey = read_verifying_key(public_key_path)?;
+    let manifest_bytes: Vec<u8>     = fs::read(manifest_path).map_err(|source| Error::io(manifest_path, source))?;
+
+    let signature_file = signature_path(manifest_path);
+    let signature_text = fs::read_to_string(&signature_file)
+        .map_err(|source| Error::signature(&signature_file, format!("cannot read signature: {}", source)))?;
+
+    check_detached_signature(&manifest_bytes, &signature_file, &signature_text, &verifying_key)
+}
+
+/// Checks a detached signature that was already read, such as one stored inside an archive,
+/// against the raw bytes of the manifest it belongs to.
+///
+/// Args:
+/// - `manifest_bytes`: The raw bytes of the manifest.
+/// - `signature_file`: The path the signature was read from, used in error messages.
+/// - `signature_text`: The base64 signature.
+/// - `verifying_key`: The Ed25519 public key the manifest must be signed with.
+///
+/// Returns:
+/// - `Result<()>`: Ok if the signature is valid, or an error if it is malformed or does not match
+///   the manifest.
+pub fn check_detached_signature(manifest_bytes: &[u8], signature_file: &Path, signature_text: &str, verifying_key: &VerifyingKey) -> Result<()> {
+    let signature = decode_signature(signature_file, signature_text)?;
+
+    verifying_key
+        .verify(manifest_bytes, &signature)
+        .map_err(|_| Error::signature(signature_file, "signature does not match the manifest"))
+}
+
+/// Decodes a base64 Ed25519 signature.
+///
+/// Args:
+/// - `path`: The file the signature was read from, used in error messages.
+/// - `text`: The base64 signature, surrounding whitespace is ignored.
+///
+/// Returns:
+/// - `Result<Signature>`: The decoded signature, or an error if it is malformed.
+fn decode_signature(path: &Path, text: &str) -> Result<Signature> {
+    let bytes = BASE64
+        .decode(text.trim())
+        .map_err(|error| Error::signature(path, format!("signature is not valid base64: {}", error)))?;
+
+    Signature::from_slice(&bytes).map_err(|_| Error::signature(path, "signature is not 64 bytes long"))
+}
+
+/// Reads an Ed25519 private key, either PKCS#8 PEM (as written by `openssl genpkey`) or a base64
+/// encoded 32-byte seed.
+///
+/// Args:
+/// - `path`: The path to the private key file.
+///
+/// Returns:
+/// - `Result<SigningKey>`: The signing key, or an error if the file is unreadable or malformed.
+pub fn read_signing_key(path: &Path) -> Result<SigningKey> {
+    let text = fs::read_to_string(path).map_err(|source| Error::io(path, source))?;
+
+    if text.contains("-----BEGIN") {
+        return SigningKey::from_pkcs8_pem(&text).map_err(|error| Error::key(path, error.to_string()));
+    }
+
+    let seed: [u8; 32] = decode_key_bytes(&text, path)?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Reads an Ed25519 public key, either SPKI PEM (as written by `openssl pkey -pubout`) or a base64
+/// encoded 32-byte key.
+///
+/// Args:
+/// - `path`: The path to the public key file.
+///
+/// Returns:
+/// - `Result<VerifyingKey>`: The verifying key, or an error if the file is unreadable or malformed.
+pub fn read_verifying_key(path: &Path) -> Result<VerifyingKey> {
+    let text = fs::read_to_string(path).map_err(|source| Error::io(path, source))?;
+
+    if text.contains("-----BEGIN") {
+        return VerifyingKey::from_public_key_pem(&text).map_err(|error| Error::key(path, error.to_string()));
+    }
+
+    let bytes: [u8; 32] = decode_key_bytes(&text, path)?;
+    VerifyingKey::from_bytes(&bytes).map_err(|error| Error::key(path, error.to_string()))
+}
+
+/// Decodes a base64 encoded 32-byte key.
+fn decode_key_bytes(text: &str, path: &Path) -> Result<[u8; 32]> {
+    BASE64
+        .decode(text.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::key(path, "neither PEM nor a base64 32-byte key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{parse_manifest, FileEntry, ManifestFormat};
+
+    const SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    /// Parses a JSON manifest without checking any signature.
+    fn manifest(json: &str) -> Manifest {
+        parse_manifest(Path::new("manifest.json"), json, Some(ManifestFormat::Json), &TrustPolicy::default()).unwrap()
+    }
+
+    #[test]
+    fn canonical_bytes_are_compact_json_with_sorted_keys() {
+        let manifest = manifest(&format!(r#"{{ "release": "1.2", "files": {{ "b.txt": "{0}", "a.txt": {{ "size": 0, "sha256": "{0}" }} }} }}"#, SHA256));
+
+        let expected = format!(r#"{{"files":{{"a.txt":{{"sha256":"{0}","size":0}},"b.txt":"{0}"}},"release":"1.2"}}"#, SHA256);
+        assert_eq!(String::from_utf8(canonical_signing_bytes(&manifest)).unwrap(), expected);
+    }
+
+    #[test]
+    fn canonical_bytes_ignore_formatting_and_key_order() {
+        let compact     = manifest(&format!(r#"{{"algorithm":"sha256","files":{{"a.txt":"{}"}},"release":"1.2"}}"#, SHA256));
+        let reformatted = manifest(&format!("{{\n  \"release\": \"1.2\",\n  \"files\": {{ \"a.txt\": \"{}\" }},\n  \"algorithm\": \"sha256\"\n}}\n", SHA256));
+
+        assert_eq!(canonical_signing_bytes(&compact), canonical_signing_bytes(&reformatted));
+    }
+
+    #[test]
+    fn canonical_bytes_leave_out_the_signature_and_cover_everything_else() {
+        let unsigned = manifest(&format!(r#"{{ "files": {{ "a.txt": "{}" }}, "release": "1.2" }}"#, SHA256));
+        let signed   = manifest(&format!(
+            r#"{{ "files": {{ "a.txt": "{}" }}, "release": "1.2", "signature": {{ "algorithm": "ed25519", "key_id": "00", "signature": "AA==" }} }}"#,
+            SHA256
+        ));
+        let released = manifest(&format!(r#"{{ "files": {{ "a.txt": "{}" }}, "release": "1.3" }}"#, SHA256));
+
+        assert_eq!(canonical_signing_bytes(&unsigned), canonical_signing_bytes(&signed));
+        assert_ne!(canonical_signing_bytes(&unsigned), canonical_signing_bytes(&released));
+    }
+
+    #[test]
+    fn embedded_signatures_cover_the_canonical_bytes() {
+        let signing_key  = SigningKey::from_bytes(&[7; 32]);
+        let mut manifest = manifest(&format!(r#"{{ "files": {{ "a.txt": "{}" }} }}"#, SHA256));
+
+        manifest.signature = Some(ManifestSignature {
+            algorithm: EMBEDDED_SIGNATURE_ALGORITHM.to_string(),
+            key_id: key_id(&signing_key.verifying_key()),
+            signature: BASE64.encode(signing_key.sign(&canonical_signing_bytes(&manifest)).to_bytes()),
+        });
+
+        let policy = TrustPolicy { trusted_keys: vec![signing_key.verifying_key()], require_signature: true };
+        assert!(check_embedded_signature(Path::new("manifest.json"), &manifest, &policy).is_ok());
+
+        manifest.files.insert("b.txt".to_string(), FileEntry::new(SHA256));
+        assert!(check_embedded_signature(Path::new("manifest.json"), &manifest, &policy).is_err());
+    }
+}
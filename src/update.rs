@@ -0,0 +1,238 @@
+use crate::error::{Error, Result};
+use crate::filter::{PathFilter, PathRules};
+use crate::manifest::{modified_nanos, record_entry, FileEntry, GenerateOptions, Manifest};
+use crate::path::{normalize_manifest_path, SymlinkPolicy};
+use crate::verify::{list_directory_files, list_empty_directories};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Options controlling how a manifest is refreshed from its directory.
+#[derive(Debug, Clone)]
+pub struct UpdateOptions {
+    /// How symlinks inside the directory are treated for entries that aren't symlink entries,
+    /// `Record` by default as for generation.
+    pub symlinks: SymlinkPolicy,
+    /// Whether entries whose path no longer exists are removed instead of kept.
+    pub prune: bool,
+    /// Which new paths are added, on top of the manifest's `ignore` list and the directory's
+    /// `.manifestignore` file.
+    pub paths: PathRules,
+}
+
+impl Default for UpdateOptions {
+    fn default() -> Self {
+        UpdateOptions { symlinks: SymlinkPolicy::Record, prune: false, paths: PathRules::default() }
+    }
+}
+
+/// What refreshing a manifest changed, with paths in manifest order.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSummary {
+    /// Paths that were not in the manifest and were added.
+    pub added: Vec<String>,
+    /// Paths whose hash, size, metadata or target was refreshed.
+    pub updated: Vec<String>,
+    /// Paths that no longer exist and were removed.
+    pub removed: Vec<String>,
+    /// Paths that no longer exist but were kept, because pruning was not requested.
+    pub missing: Vec<String>,
+    /// The number of entries that were checked and found unchanged.
+    pub unchanged: usize,
+    /// Whether an embedded signature was dropped because the manifest changed.
+    pub signature_removed: bool,
+}
+
+impl UpdateSummary {
+    /// Returns `true` if the manifest was changed.
+    pub fn is_changed(&self) -> bool {
+        !self.added.is_empty() || !self.updated.is_empty() || !self.removed.is_empty()
+    }
+}
+
+/// Refreshes a manifest from its directory in place, rehashing only what may have changed.
+///
+/// Files are only skipped if their entry records a size and mtime, and both still match; every
+/// other file entry is rehashed. Symlink and directory entries are always checked, as that needs
+/// no hashing. Refreshed entries keep the fields they record, such as sizes and metadata,
+/// and existing entries keep their order. New paths are appended, recorded with the manifest's
+/// algorithm and the details its existing entries record.
+///
+/// If anything changed, an embedded signature no longer matches and is removed.
+///
+/// Args:
+/// - `directory_path`: Path to the directory the manifest describes.
+/// - `manifest`: The manifest to refresh.
+/// - `options`: Options controlling which files are rehashed, symlink handling, pruning and which
+///   new paths are added.
+///
+/// Returns:
+/// - `Result<UpdateSummary>`: What was changed, or an error if walking or hashing failed, or a path
+///   is rejected by the symlink policy.
+pub fn update_manifest(directory_path: &Path, manifest: &mut Manifest, options: &UpdateOptions) -> Result<UpdateSummary> {
+    let mut summary = UpdateSummary::default();
+    let mut pruned: Vec<String> = Vec::new();
+
+    for (key, entry) in manifest.files.iter_mut() {
+        let relative_path = normalize_manifest_path(key).unwrap_or_else(|| key.clone());
+
+        if entry.hash.is_some() && !is_stale(&directory_path.join(&relative_path), entry)? {
+            summary.unchanged += 1;
+            continue;
+        }
+
+        // Rehash with the entry's own algorithm, recording the same details it already has.
+        let generate_options = GenerateOptions {
+            algorithm: entry.algorithm.or(manifest.algorithm).unwrap_or_default(),
+            symlinks: if entry.target.is_some() { SymlinkPolicy::Record } else { options.symlinks },
+            record_sizes: entry.size.is_some(),
+            record_mtimes: entry.mtime.is_some(),
+            record_metadata: entry.mode.is_some(),
+            paths: PathRules::default(),
+        };
+
+        match record_entry(directory_path, &relative_path, &generate_options)? {
+            None if options.prune => pruned.push(key.clone()),
+            None => summary.missing.push(key.clone()),
+            Some(mut refreshed) => {
+                // Keep naming the algorithm where the entry did, and keep a recorded type, so the
+                // manifest doesn't churn.
+                if entry.algorithm.is_none() {
+                    refreshed.algorithm = None;
+                }
+                refreshed.kind = refreshed.kind.or(entry.kind);
+
+                if refreshed == *entry {
+                    summary.unchanged += 1;
+                } else {
+                    *entry = refreshed;
+                    summary.updated.push(key.clone());
+                }
+            }
+        }
+    }
+
+    for key in &pruned {
+        manifest.files.shift_remove(key);
+    }
+    summary.removed = pruned;
+
+    // Add the paths that the manifest doesn't list yet, after the existing entries.
+    let listed_paths: HashSet<String> = manifest.files.keys().filter_map(|key| normalize_manifest_path(key)).collect();
+    let generate_options = GenerateOptions {
+        algorithm: manifest.files.values().find(|entry| entry.hash.is_some()).map_or_else(
+            || manifest.algorithm.unwrap_or_default(),
+            |entry| manifest.algorithm_for(entry),
+        ),
+        symlinks: options.symlinks,
+        record_sizes: manifest.files.values().any(|entry| entry.size.is_some()),
+        record_mtimes: manifest.files.values().any(|entry| entry.mtime.is_some()),
+        record_metadata: manifest.files.values().any(|entry| entry.mode.is_some()),
+        paths: PathRules::default(),
+    };
+
+    let filter            = PathFilter::new(directory_path, &options.paths, &manifest.ignore)?;
+    let file_paths        = list_directory_files(directory_path, options.symlinks, &filter)?;
+    let empty_directories = list_empty_directories(directory_path, &filter)?;
+
+    for relative_path in file_paths.into_iter().chain(empty_directories) {
+        if listed_paths.contains(&relative_path) {
+            continue;
+        }
+
+        if let Some(mut entry) = record_entry(directory_path, &relative_path, &generate_options)? {
+            // Plain entries name their algorithm if it isn't the manifest-wide one.
+            if entry.hash.is_some() && manifest.algorithm_for(&entry) != generate_options.algorithm {
+                entry.algorithm = Some(generate_options.algorithm);
+            }
+
+            manifest.files.insert(relative_path.clone(), entry);
+            summary.added.push(relative_path);
+        }
+    }
+
+    if summary.is_changed() && manifest.signature.take().is_some() {
+        summary.signature_removed = true;
+    }
+
+    Ok(summary)
+}
+
+/// Returns `true` if a file may have changed since its entry was recorded: the entry doesn't
+/// record both its size and mtime, either of them differs, or the file isn't a regular file.
+///
+/// Args:
+/// - `file_path`: Path to the file.
+/// - `entry`: The manifest entry of the file.
+///
+/// Returns:
+/// - `Result<bool>`: Whether the file should be rehashed, or an error if it could not be inspected.
+fn is_stale(file_path: &Path, entry: &FileEntry) -> Result<bool> {
+    let (Some(size), Some(mtime)) = (entry.size, entry.mtime) else {
+        return Ok(true);
+    };
+
+    let metadata = match fs::metadata(file_path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(true),
+        Err(error) => return Err(Error::io(file_path, error)),
+    };
+
+    Ok(!metadata.is_file() || metadata.len() != size || modified_nanos(&metadata) != Some(mtime))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{generate_manifest, read_manifest, write_manifest};
+    use crate::signature::TrustPolicy;
+
+    #[test]
+    fn updating_a_manifest_inside_its_directory_twice_changes_nothing_the_second_time() {
+        let directory     = tempfile::tempdir().unwrap();
+        let manifest_path = directory.path().join("MANIFEST.json");
+        fs::write(directory.path().join("a.txt"), "a").unwrap();
+
+        let mut options = UpdateOptions::default();
+        options.paths.exclude_manifest(directory.path(), &manifest_path);
+
+        let generate_options = GenerateOptions { record_mtimes: true, paths: options.paths.clone(), ..GenerateOptions::default() };
+        write_manifest(&manifest_path, &generate_manifest(directory.path(), &generate_options).unwrap()).unwrap();
+        fs::write(directory.path().join("MANIFEST.json.sig"), "signature").unwrap();
+        fs::write(directory.path().join("b.txt"), "b").unwrap();
+
+        let mut manifest = read_manifest(&manifest_path, &TrustPolicy::default()).unwrap();
+        let first        = update_manifest(directory.path(), &mut manifest, &options).unwrap();
+        assert_eq!(first.added, ["b.txt"]);
+        assert!(first.updated.is_empty());
+        write_manifest(&manifest_path, &manifest).unwrap();
+
+        let mut manifest = read_manifest(&manifest_path, &TrustPolicy::default()).unwrap();
+        let second       = update_manifest(directory.path(), &mut manifest, &options).unwrap();
+        assert!(!second.is_changed());
+        assert_eq!(second.unchanged, 2);
+    }
+
+    #[test]
+    fn files_changed_without_a_new_size_or_mtime_are_rehashed() {
+        let directory = tempfile::tempdir().unwrap();
+        let file_path = directory.path().join("a.txt");
+        fs::write(&file_path, "a").unwrap();
+
+        let generate_options = GenerateOptions { record_mtimes: true, ..GenerateOptions::default() };
+        let mut manifest     = generate_manifest(directory.path(), &generate_options).unwrap();
+        let recorded_mtime   = fs::metadata(&file_path).unwrap().modified().unwrap();
+
+        // Same size, and the old mtime put back, as a copy preserving timestamps would.
+        fs::write(&file_path, "b").unwrap();
+        fs::File::options().write(true).open(&file_path).unwrap().set_modified(recorded_mtime).unwrap();
+
+        let unchanged = update_manifest(directory.path(), &mut manifest, &UpdateOptions::default()).unwrap();
+        assert!(!unchanged.is_changed(), "size and mtime match, so the file is trusted");
+
+        manifest.files["a.txt"].mtime = None;
+        let rehashed = update_manifest(directory.path(), &mut manifest, &UpdateOptions::default()).unwrap();
+        assert_eq!(rehashed.updated, ["a.txt"]);
+    }
+}
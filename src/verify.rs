@@ -0,0 +1,578 @@
+use crate::error::{Error, Result};
+use crate::filter::{PathFilter, PathRules};
+use crate::hash::{hash_bytes, hash_file};
+use crate::manifest::{FileEntry, Manifest};
+use crate::metadata::{metadata_mismatches, read_metadata, EntryType};
+use crate::path::{manifest_key, match_case, normalize_manifest_path, resolve_entry, ResolvedPath, SymlinkPolicy};
+use crate::report::{FileReport, FileStatus, VerificationReport};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use walkdir::WalkDir;
+
+/// Options controlling how a directory is verified.
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// Whether files not listed in the manifest should be reported as extra.
+    pub strict: bool,
+    /// The number of files hashed concurrently, at least one.
+    pub jobs: usize,
+    /// How symlinks inside the directory are treated.
+    pub symlinks: SymlinkPolicy,
+    /// Whether the type, permission bits and ownership recorded in entries are ignored.
+    pub ignore_metadata: bool,
+    /// Whether manifest paths match files whose names differ only in case, for manifests built on
+    /// case-insensitive file systems.
+    pub case_insensitive: bool,
+    /// Which unlisted paths are reported in strict mode, on top of the manifest's `ignore` list and
+    /// the directory's `.manifestignore` file.
+    pub paths: PathRules,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            strict: false,
+            jobs: default_jobs(),
+            symlinks: SymlinkPolicy::default(),
+            ignore_metadata: false,
+            case_insensitive: false,
+            paths: PathRules::default(),
+        }
+    }
+}
+
+/// Returns the default number of hashing jobs, one per available CPU.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map(|count| count.get()).unwrap_or(1)
+}
+
+/// Verifies each file listed in the manifest exists in the directory and matches the recorded hash.
+/// In strict mode, any file in the directory that is not listed in the manifest is also reported,
+/// unless it is left out by `options.paths`, the manifest's `ignore` list or the directory's
+/// `.manifestignore` file. The ignore file is only honored if the manifest lists it and its hash
+/// matches, as the directory being verified could otherwise hide the files it ships.
+///
+/// Manifest paths are resolved inside the directory according to `options.symlinks`, and entries
+/// that would escape it are reported as unsafe instead of being read.
+///
+/// Entries are hashed on `options.jobs` threads. The report is sorted by path, so its order never
+/// depends on which thread finishes first.
+///
+/// Args:
+/// - `directory_path`: Path to the directory containing the files to verify.
+/// - `manifest`: The manifest containing expected file hashes.
+/// - `options`: Options controlling strict mode, concurrency, symlink and metadata handling.
+///
+/// Returns:
+/// - `Result<VerificationReport>`: The report for every checked path, or an error if the directory
+///   could not be walked or the path rules are invalid in strict mode.
+pub fn verify_directory(directory_path: &Path, manifest: &Manifest, options: &VerifyOptions) -> Result<VerificationReport> {
+    let mut report = VerificationReport::default();
+
+    // Hand the manifest entries out to the worker threads one at a time.
+    let entries: Vec<(&String, &FileEntry)> = manifest.files.iter().collect();
+    let next_entry = AtomicUsize::new(0);
+    let jobs       = options.jobs.clamp(1, entries.len().max(1));
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut file_reports: Vec<FileReport> = Vec::new();
+
+                    while let Some((path, entry)) = entries.get(next_entry.fetch_add(1, Ordering::Relaxed)) {
+                        file_reports.push(verify_entry(directory_path, manifest, path, entry, options));
+                    }
+
+                    file_reports
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            report.files.extend(worker.join().expect("hashing thread panicked"));
+        }
+    });
+
+    // In strict mode, walk the whole directory and report every file the manifest doesn't list.
+    if options.strict {
+        let filter = PathFilter::for_verification(directory_path, &options.paths, manifest)?;
+
+        for unlisted_path in find_unlisted_files(directory_path, manifest, options, &filter)? {
+            report.files.push(FileReport::new(unlisted_path, FileStatus::Extra));
+        }
+    }
+
+    // Keep the report independent of the manifest's map ordering.
+    report.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(report)
+}
+
+/// Checks a single manifest entry against the file in the directory. The content is checked first;
+/// if it matches, the type, permission bits and ownership recorded in the entry are checked next.
+///
+/// Args:
+/// - `directory_path`: Path to the directory containing the files to verify.
+/// - `manifest`: The manifest the entry belongs to.
+/// - `expected_path_str`: The manifest path of the entry.
+/// - `entry`: The expected hash of the file.
+/// - `options`: Options controlling symlink and metadata handling.
+///
+/// Returns:
+/// - `FileReport`: The verification result for the entry.
+fn verify_entry(directory_path: &Path, manifest: &Manifest, expected_path_str: &str, entry: &FileEntry, options: &VerifyOptions) -> FileReport {
+    let algorithm = manifest.algorithm_for(entry);
+
+    let mut file_report = FileReport::new(expected_path_str, FileStatus::Missing);
+    file_report.expected_size = entry.size;
+
+    // Manifests are validated on read, so a path that escapes the directory is a caller error.
+    let Some(mut normalized_path) = normalize_manifest_path(expected_path_str) else {
+        file_report.status = FileStatus::Unsafe;
+        file_report.error  = Some("path escapes the verified directory".to_string());
+        return file_report;
+    };
+    if options.case_insensitive {
+        normalized_path = match_case(directory_path, &normalized_path);
+    }
+
+    // Directory entries are checked for existence, and symlink entries by their target.
+    let expected_hash = match (&entry.hash, entry.kind) {
+        (Some(expected_hash), _) => expected_hash,
+        (None, Some(EntryType::Directory)) => {
+            verify_directory_entry(directory_path, &normalized_path, entry, options, &mut file_report);
+            return file_report;
+        }
+        (None, _) => {
+            verify_symlink_entry(directory_path, &normalized_path, entry, options, &mut file_report);
+            return file_report;
+        }
+    };
+
+    file_report.algorithm     = Some(algorithm);
+    file_report.expected_hash = Some(expected_hash.clone());
+
+    // Hash whatever the path safely resolves to, and compare against the expected hash. If the
+    // entry records a size, a file of the wrong size fails without being hashed.
+    let hash = match resolve_entry(directory_path, &normalized_path, options.symlinks) {
+        Ok(ResolvedPath::Missing) => return file_report,
+        Ok(ResolvedPath::File(file_path)) => match fs::metadata(&file_path) {
+            Ok(metadata) if entry.size.is_some_and(|size| size != metadata.len()) => {
+                file_report.status      = FileStatus::SizeMismatch;
+                file_report.actual_size = Some(metadata.len());
+                return file_report;
+            }
+            Ok(metadata) => {
+                file_report.actual_size = entry.size.map(|_| metadata.len());
+                hash_file(&file_path, algorithm)
+                    .inspect(|_| file_report.hashed_bytes = metadata.len())
+                    .map_err(|error| error.to_string())
+            }
+            Err(error) => Err(Error::io(&file_path, error).to_string()),
+        },
+        Ok(ResolvedPath::Symlink(target)) => {
+            let target_size = target.len() as u64;
+            if entry.size.is_some_and(|size| size != target_size) {
+                file_report.status      = FileStatus::SizeMismatch;
+                file_report.actual_size = Some(target_size);
+                file_report.link_target = Some(target);
+                return file_report;
+            }
+
+            let hash = hash_bytes(target.as_bytes(), algorithm);
+            file_report.actual_size = entry.size.map(|_| target_size);
+            file_report.link_target = Some(target);
+            Ok(hash)
+        }
+        Ok(ResolvedPath::Directory) => Err("is a directory".to_string()),
+        Ok(ResolvedPath::Unsafe(reason)) => {
+            file_report.status = FileStatus::Unsafe;
+            file_report.error  = Some(reason);
+            return file_report;
+        }
+        Err(error) => Err(Error::io(&directory_path.join(&normalized_path), error).to_string()),
+    };
+
+    match hash {
+        Ok(hash) => {
+            file_report.status = if hash.eq_ignore_ascii_case(expected_hash) {
+                FileStatus::Matched
+            } else {
+                FileStatus::Mismatched
+            };
+            file_report.actual_hash = Some(hash);
+
+            check_metadata(directory_path, &normalized_path, entry, options, &mut file_report);
+        }
+        Err(error) => {
+            file_report.status = FileStatus::Unreadable;
+            file_report.error  = Some(error);
+        }
+    }
+
+    file_report
+}
+
+/// Checks that a directory entry names a directory.
+///
+/// Args:
+/// - `directory_path`: Path to the directory containing the files to verify.
+/// - `normalized_path`: The normalized manifest path of the entry.
+/// - `entry`: The directory entry.
+/// - `options`: Options controlling symlink and metadata handling.
+/// - `file_report`: The report to fill in for the entry.
+fn verify_directory_entry(
+    directory_path: &Path,
+    normalized_path: &str,
+    entry: &FileEntry,
+    options: &VerifyOptions,
+    file_report: &mut FileReport,
+) {
+    file_report.status = match resolve_entry(directory_path, normalized_path, options.symlinks) {
+        Ok(ResolvedPath::Missing) => return,
+        Ok(ResolvedPath::Directory) => FileStatus::Matched,
+        Ok(ResolvedPath::File(_)) => {
+            file_report.error = Some("expected a directory, found a file".to_string());
+            FileStatus::Mismatched
+        }
+        Ok(ResolvedPath::Symlink(target)) => {
+            file_report.error       = Some("expected a directory, found a symlink".to_string());
+            file_report.link_target = Some(target);
+            FileStatus::Mismatched
+        }
+        Ok(ResolvedPath::Unsafe(reason)) => {
+            file_report.status = FileStatus::Unsafe;
+            file_report.error  = Some(reason);
+            return;
+        }
+        Err(error) => {
+            file_report.status = FileStatus::Unreadable;
+            file_report.error  = Some(Error::io(&directory_path.join(normalized_path), error).to_string());
+            return;
+        }
+    };
+
+    check_metadata(directory_path, normalized_path, entry, options, file_report);
+}
+
+/// Checks a symlink entry by comparing its link target exactly. The link is never followed, and
+/// under the `Forbid` policy the entry is reported as unsafe.
+///
+/// Args:
+/// - `directory_path`: Path to the directory containing the files to verify.
+/// - `normalized_path`: The normalized manifest path of the entry.
+/// - `entry`: The symlink entry, with its expected target.
+/// - `options`: Options controlling symlink and metadata handling.
+/// - `file_report`: The report to fill in for the entry.
+fn verify_symlink_entry(
+    directory_path: &Path,
+    normalized_path: &str,
+    entry: &FileEntry,
+    options: &VerifyOptions,
+    file_report: &mut FileReport,
+) {
+    let expected_target = entry.target.clone().unwrap_or_default();
+    let policy = match options.symlinks {
+        SymlinkPolicy::Forbid => SymlinkPolicy::Forbid,
+        SymlinkPolicy::Follow | SymlinkPolicy::Record => SymlinkPolicy::Record,
+    };
+
+    file_report.status = match resolve_entry(directory_path, normalized_path, policy) {
+        Ok(ResolvedPath::Missing) => return,
+        Ok(ResolvedPath::Symlink(target)) => {
+            let status = if target == expected_target { FileStatus::Matched } else { FileStatus::Mismatched };
+            file_report.link_target = Some(target);
+            status
+        }
+        Ok(ResolvedPath::File(_)) => {
+            file_report.error = Some("expected a symlink, found a file".to_string());
+            FileStatus::Mismatched
+        }
+        Ok(ResolvedPath::Directory) => {
+            file_report.error = Some("expected a symlink, found a directory".to_string());
+            FileStatus::Mismatched
+        }
+        Ok(ResolvedPath::Unsafe(reason)) => {
+            file_report.status = FileStatus::Unsafe;
+            file_report.error  = Some(reason);
+            return;
+        }
+        Err(error) => {
+            file_report.status = FileStatus::Unreadable;
+            file_report.error  = Some(Error::io(&directory_path.join(normalized_path), error).to_string());
+            return;
+        }
+    };
+    file_report.expected_target = Some(expected_target);
+
+    check_metadata(directory_path, normalized_path, entry, options, file_report);
+}
+
+/// Checks the type, permission bits and ownership recorded in an entry whose content has been
+/// checked, unless metadata is ignored. A matching entry with metadata differences becomes a
+/// metadata mismatch.
+///
+/// Args:
+/// - `directory_path`: Path to the directory containing the files to verify.
+/// - `normalized_path`: The normalized manifest path of the entry.
+/// - `entry`: The entry with the expected metadata.
+/// - `options`: Options controlling metadata handling.
+/// - `file_report`: The report to update for the entry.
+fn check_metadata(directory_path: &Path, normalized_path: &str, entry: &FileEntry, options: &VerifyOptions, file_report: &mut FileReport) {
+    if !options.ignore_metadata {
+        match read_metadata(&directory_path.join(normalized_path)) {
+            Ok(metadata) => file_report.metadata_mismatches = metadata_mismatches(entry, &metadata),
+            Err(error) => {
+                file_report.status = FileStatus::Unreadable;
+                file_report.error  = Some(Error::io(&directory_path.join(normalized_path), error).to_string());
+            }
+        }
+    }
+
+    if file_report.status == FileStatus::Matched && !file_report.metadata_mismatches.is_empty() {
+        file_report.status = FileStatus::MetadataMismatch;
+    }
+}
+
+/// Walks the directory and collects the relative paths of all files not listed in the manifest.
+///
+/// Args:
+/// - `directory_path`: Path to the directory to walk.
+/// - `manifest`: The manifest containing expected file hashes.
+/// - `options`: Options controlling symlink handling and whether case is ignored.
+/// - `filter`: Which paths are expected to be unlisted and left out.
+///
+/// Returns:
+/// - `Result<Vec<String>>`: Sorted relative paths of unlisted files, or an error.
+fn find_unlisted_files(directory_path: &Path, manifest: &Manifest, options: &VerifyOptions, filter: &PathFilter) -> Result<Vec<String>> {
+    let comparable = |path: String| if options.case_insensitive { path.to_lowercase() } else { path };
+
+    let listed_paths: HashSet<String> =
+        manifest.files.keys().filter_map(|key| normalize_manifest_path(key)).map(comparable).collect();
+
+    let unlisted_paths: Vec<String> = list_directory_files(directory_path, options.symlinks, filter)?
+        .into_iter()
+        .filter(|relative_path| !listed_paths.contains(&comparable(relative_path.clone())))
+        .collect();
+
+    Ok(unlisted_paths)
+}
+
+/// Walks the directory and collects the relative paths of all files it contains.
+///
+/// Symlinks are never descended into. With `Follow`, symlinks to files are listed like files; with
+/// `Record` and `Forbid`, every symlink is listed so it can be recorded or rejected. Paths the filter
+/// leaves out are not listed.
+///
+/// Args:
+/// - `directory_path`: Path to the directory to walk.
+/// - `symlinks`: How symlinks inside the directory are treated.
+/// - `filter`: Which paths are left out.
+///
+/// Returns:
+/// - `Result<Vec<String>>`: Sorted forward-slash relative paths of all files, or an error.
+pub(crate) fn list_directory_files(directory_path: &Path, symlinks: SymlinkPolicy, filter: &PathFilter) -> Result<Vec<String>> {
+    let mut relative_paths: Vec<String> = Vec::new();
+
+    for entry in WalkDir::new(directory_path).sort_by_file_name() {
+        let entry = entry?;
+        let file_type = entry.file_type();
+
+        let listed = if file_type.is_symlink() {
+            symlinks != SymlinkPolicy::Follow || fs::metadata(entry.path()).is_ok_and(|metadata| metadata.is_file())
+        } else {
+            file_type.is_file()
+        };
+
+        if listed {
+            // Manifest keys are relative to the directory and always use forward slashes.
+            let relative_path = manifest_key(directory_path, entry.path());
+            if !filter.is_excluded(&relative_path, false) {
+                relative_paths.push(relative_path);
+            }
+        }
+    }
+
+    Ok(relative_paths)
+}
+
+/// Walks the directory and collects the relative paths of all empty directories below it.
+///
+/// Symlinks are never descended into, so only real directories are listed. Paths the filter leaves
+/// out are not listed.
+///
+/// Args:
+/// - `directory_path`: Path to the directory to walk.
+/// - `filter`: Which paths are left out.
+///
+/// Returns:
+/// - `Result<Vec<String>>`: Sorted forward-slash relative paths of empty directories, or an error.
+pub(crate) fn list_empty_directories(directory_path: &Path, filter: &PathFilter) -> Result<Vec<String>> {
+    let mut relative_paths: Vec<String> = Vec::new();
+
+    for entry in WalkDir::new(directory_path).min_depth(1).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+
+        let mut children = fs::read_dir(entry.path()).map_err(|source| Error::io(entry.path(), source))?;
+        let relative_path = manifest_key(directory_path, entry.path());
+        if children.next().is_none() && !filter.is_excluded(&relative_path, true) {
+            relative_paths.push(relative_path);
+        }
+    }
+
+    Ok(relative_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashAlgorithm;
+    use crate::manifest::{read_manifest, ManifestFormat};
+    use crate::signature::TrustPolicy;
+    use indexmap::IndexMap;
+
+    /// Writes the files into a new directory, returning it with a manifest listing them.
+    fn directory_with(files: &[(&str, &str)]) -> (tempfile::TempDir, Manifest) {
+        let directory = tempfile::tempdir().unwrap();
+        let mut listed: IndexMap<String, FileEntry> = IndexMap::new();
+
+        for (path, contents) in files {
+            let file_path = directory.path().join(path);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(&file_path, contents).unwrap();
+            listed.insert(path.to_string(), FileEntry::new(hash_bytes(contents.as_bytes(), HashAlgorithm::Sha256)));
+        }
+
+        let manifest = Manifest {
+            algorithm: None,
+            files: listed,
+            ignore: Vec::new(),
+            signature: None,
+            extra: IndexMap::new(),
+            format: ManifestFormat::Json,
+            warnings: Vec::new(),
+        };
+
+        (directory, manifest)
+    }
+
+    /// Returns the options for a strict check on one thread.
+    fn strict() -> VerifyOptions {
+        VerifyOptions { strict: true, jobs: 1, ..VerifyOptions::default() }
+    }
+
+    /// Returns each path of the report with its status.
+    fn statuses(report: &VerificationReport) -> Vec<(&str, FileStatus)> {
+        report.files.iter().map(|file| (file.path.as_str(), file.status)).collect()
+    }
+
+    #[test]
+    fn listed_files_match() {
+        let (directory, manifest) = directory_with(&[("a.txt", "a"), ("dir/b.txt", "b")]);
+
+        let report = verify_directory(directory.path(), &manifest, &strict()).unwrap();
+        assert_eq!(statuses(&report), [("a.txt", FileStatus::Matched), ("dir/b.txt", FileStatus::Matched)]);
+        assert!(report.is_success());
+    }
+
+    #[test]
+    fn strict_mode_reports_unlisted_files_as_extra() {
+        let (directory, manifest) = directory_with(&[("a.txt", "a")]);
+        fs::create_dir(directory.path().join("dir")).unwrap();
+        fs::write(directory.path().join("dir/extra.txt"), "extra").unwrap();
+
+        let report = verify_directory(directory.path(), &manifest, &strict()).unwrap();
+        assert_eq!(statuses(&report), [("a.txt", FileStatus::Matched), ("dir/extra.txt", FileStatus::Extra)]);
+        assert!(!report.is_success());
+
+        let lenient = verify_directory(directory.path(), &manifest, &VerifyOptions { strict: false, ..strict() }).unwrap();
+        assert_eq!(statuses(&lenient), [("a.txt", FileStatus::Matched)]);
+    }
+
+    #[test]
+    fn listed_files_that_are_gone_are_missing() {
+        let (directory, manifest) = directory_with(&[("a.txt", "a"), ("b.txt", "b")]);
+        fs::remove_file(directory.path().join("b.txt")).unwrap();
+
+        let report = verify_directory(directory.path(), &manifest, &strict()).unwrap();
+        assert_eq!(statuses(&report), [("a.txt", FileStatus::Matched), ("b.txt", FileStatus::Missing)]);
+    }
+
+    #[test]
+    fn ignored_files_are_not_reported() {
+        let (directory, mut manifest) = directory_with(&[("a.txt", "a")]);
+        fs::write(directory.path().join("build.log"), "log").unwrap();
+        fs::create_dir(directory.path().join("tmp")).unwrap();
+        fs::write(directory.path().join("tmp/scratch"), "scratch").unwrap();
+        manifest.ignore.push("*.log".to_string());
+
+        let mut options = strict();
+        options.paths.exclude.push("/tmp".to_string());
+
+        let report = verify_directory(directory.path(), &manifest, &options).unwrap();
+        assert_eq!(statuses(&report), [("a.txt", FileStatus::Matched)]);
+    }
+
+    #[test]
+    fn report_is_the_same_for_any_number_of_jobs() {
+        let contents: Vec<(String, String)> = (0..64).map(|index| (format!("dir{}/file{}.txt", index % 4, index), index.to_string())).collect();
+        let files: Vec<(&str, &str)>        = contents.iter().map(|(path, contents)| (path.as_str(), contents.as_str())).collect();
+
+        let (directory, mut manifest) = directory_with(&files);
+        fs::write(directory.path().join("dir1/file5.txt"), "changed").unwrap();
+        fs::remove_file(directory.path().join("dir2/file10.txt")).unwrap();
+        fs::write(directory.path().join("dir3/extra.txt"), "extra").unwrap();
+        manifest.files.reverse();
+
+        let reports: Vec<serde_json::Value> = [1, 8]
+            .into_iter()
+            .map(|jobs| {
+                let report = verify_directory(directory.path(), &manifest, &VerifyOptions { jobs, ..strict() }).unwrap();
+                serde_json::to_value(&report.files).unwrap()
+            })
+            .collect();
+
+        assert_eq!(reports[0], reports[1]);
+        assert_eq!(reports[0].as_array().unwrap().len(), 65);
+    }
+
+    #[test]
+    fn case_insensitive_matching_finds_files_named_in_another_case() {
+        let (directory, mut manifest) = directory_with(&[("Dir/Readme.TXT", "a")]);
+        let entry = manifest.files.shift_remove("Dir/Readme.TXT").unwrap();
+        manifest.files.insert("dir/README.txt".to_string(), entry);
+
+        let report = verify_directory(directory.path(), &manifest, &strict()).unwrap();
+        assert_eq!(statuses(&report), [("Dir/Readme.TXT", FileStatus::Extra), ("dir/README.txt", FileStatus::Missing)]);
+
+        let options = VerifyOptions { case_insensitive: true, ..strict() };
+        let report  = verify_directory(directory.path(), &manifest, &options).unwrap();
+        assert_eq!(statuses(&report), [("dir/README.txt", FileStatus::Matched)]);
+        assert!(report.is_success());
+    }
+
+    #[test]
+    fn backslash_paths_are_verified_and_reported_with_forward_slashes() {
+        let (directory, manifest) = directory_with(&[("lib/net6.0/app.dll", "app")]);
+        let manifest_path         = directory.path().join("MANIFEST.json");
+        let hash                  = manifest.files["lib/net6.0/app.dll"].hash.clone().unwrap();
+        fs::write(&manifest_path, format!(r#"{{"files": {{"lib\\net6.0\\app.dll": "{}"}}}}"#, hash)).unwrap();
+
+        let manifest = read_manifest(&manifest_path, &TrustPolicy::default()).unwrap();
+        assert_eq!(manifest.warnings.len(), 1);
+        assert!(manifest.warnings[0].ends_with(r#"1 path(s) use backslash separators and were normalized to forward slashes, such as "lib\net6.0\app.dll""#));
+
+        let mut options = strict();
+        options.paths.exclude_manifest(directory.path(), &manifest_path);
+
+        let report = verify_directory(directory.path(), &manifest, &options).unwrap();
+        assert_eq!(statuses(&report), [("lib/net6.0/app.dll", FileStatus::Matched)]);
+    }
+}